@@ -0,0 +1,313 @@
+//! Boolean operations on outlines.
+//!
+//! Both outlines are flattened and all edges are split at their intersections,
+//! including those of an outline with itself. Edges closer than a small fraction of
+//! the tolerance are snapped together, so coincident edges end up identical.
+//! Each remaining edge is kept if the result is inside on exactly one side of it,
+//! which is decided by casting axis aligned rays from the edge to both sides.
+//! Contours with non-finite points are ignored.
+//! The kept edges are oriented with the inside on the left and linked into closed contours.
+
+use crate::{Outline, PathBuilder, ErrorPolicy, FillRule, Vector};
+use crate::flatten::polylines;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BooleanOp {
+    /// inside of either outline
+    Union,
+    /// inside of both outlines
+    Intersection,
+    /// inside of the first but not the second outline
+    Difference,
+    /// inside of exactly one outline
+    Xor,
+}
+impl BooleanOp {
+    #[inline]
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            BooleanOp::Union => a || b,
+            BooleanOp::Intersection => a && b,
+            BooleanOp::Difference => a && !b,
+            BooleanOp::Xor => a != b,
+        }
+    }
+}
+
+#[inline]
+fn is_inside(winding: i32, fill_rule: FillRule) -> bool {
+    match fill_rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+#[inline]
+fn cross(a: Vector, b: Vector) -> f32 {
+    a.x() * b.y() - a.y() * b.x()
+}
+
+// identifies a point exactly
+type Key = (u32, u32);
+#[inline]
+fn key(p: Vector) -> Key {
+    // + 0.0 turns -0.0 into 0.0
+    ((p.x() + 0.0).to_bits(), (p.y() + 0.0).to_bits())
+}
+// identifies an edge regardless of its direction
+#[inline]
+fn edge_key(a: Vector, b: Vector) -> (Key, Key) {
+    let (ka, kb) = (key(a), key(b));
+    if ka < kb { (ka, kb) } else { (kb, ka) }
+}
+
+#[derive(Copy, Clone)]
+struct Edge {
+    from: Vector,
+    to: Vector,
+    // 0 for the first outline, 1 for the second
+    owner: usize,
+}
+
+// the closed polygons of the outline, snapped to a grid of `eps`
+fn edges(outline: &impl Outline, tolerance: f32, eps: f32, owner: usize, out: &mut Vec<Edge>) {
+    // per component, `Vector::round` goes through i32 and saturates
+    let snap = |p: Vector| Vector::new((p.x() / eps).round() * eps, (p.y() / eps).round() * eps);
+    for line in polylines(outline, tolerance) {
+        let points: Vec<Vector> = line.points.into_iter().map(snap).collect();
+        if points.iter().any(|p| !p.x().is_finite() || !p.y().is_finite()) {
+            continue;
+        }
+        // filling always closes the contour
+        let closing = Some((points[points.len() - 1], points[0]));
+        for (from, to) in points.windows(2).map(|w| (w[0], w[1])).chain(closing) {
+            if from != to {
+                out.push(Edge { from, to, owner });
+            }
+        }
+    }
+}
+
+// the distance of `p` to the segment and the parameter of the closest point
+fn project(p: Vector, from: Vector, to: Vector) -> (f32, f32) {
+    let d = to - from;
+    let t = ((p - from).dot(d) / d.dot(d)).clamp(0.0, 1.0);
+    ((from.lerp(to, t) - p).length(), t)
+}
+
+// split all edges where they cross or touch another edge
+fn split(edges: &[Edge], eps: f32) -> Vec<Edge> {
+    let mut splits: Vec<Vec<(f32, Vector)>> = vec![vec![]; edges.len()];
+
+    // sort by the left end so only overlapping ranges have to be compared
+    let mut order: Vec<usize> = (0 .. edges.len()).collect();
+    let min_x = |e: &Edge| e.from.x().min(e.to.x());
+    let max_x = |e: &Edge| e.from.x().max(e.to.x());
+    order.sort_by(|&i, &j| min_x(&edges[i]).total_cmp(&min_x(&edges[j])));
+
+    for (n, &i) in order.iter().enumerate() {
+        let a = edges[i];
+        for &j in &order[n + 1 ..] {
+            let b = edges[j];
+            if min_x(&b) > max_x(&a) + eps {
+                break;
+            }
+            if b.from.y().min(b.to.y()) > a.from.y().max(a.to.y()) + eps
+                || a.from.y().min(a.to.y()) > b.from.y().max(b.to.y()) + eps {
+                continue;
+            }
+
+            // endpoints that lie on the other edge, which covers touching and overlapping edges
+            let mut touching = false;
+            for &(e, f, p) in &[(i, a, b.from), (i, a, b.to), (j, b, a.from), (j, b, a.to)] {
+                let (distance, t) = project(p, f.from, f.to);
+                if distance <= eps {
+                    touching = true;
+                    if p != f.from && p != f.to {
+                        splits[e].push((t, p));
+                    }
+                }
+            }
+            if touching {
+                continue;
+            }
+
+            // proper crossing
+            let r = a.to - a.from;
+            let s = b.to - b.from;
+            let d = cross(r, s);
+            if d == 0.0 {
+                continue;
+            }
+            let t = cross(b.from - a.from, s) / d;
+            let u = cross(b.from - a.from, r) / d;
+            if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+                let p = a.from.lerp(a.to, t);
+                splits[i].push((t, p));
+                splits[j].push((u, p));
+            }
+        }
+    }
+
+    let mut out = vec![];
+    for (edge, mut points) in edges.iter().zip(splits) {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut from = edge.from;
+        for p in points.into_iter().map(|(_, p)| p).chain(Some(edge.to)) {
+            if p != from {
+                out.push(Edge { from, to: p, owner: edge.owner });
+                from = p;
+            }
+        }
+    }
+    out
+}
+
+// the edges grouped into bands by their range along one axis,
+// so a ray across the bands only has to look at the edges in one of them.
+// the bands hold about the same number of endpoints, however the edges are distributed.
+struct Bands {
+    // the lower end of every band but the first
+    bounds: Vec<f32>,
+    bands: Vec<Vec<usize>>,
+}
+impl Bands {
+    fn new(edges: &[Edge], axis: fn(Vector) -> f32) -> Bands {
+        let mut coords: Vec<f32> = edges.iter().flat_map(|e| vec![axis(e.from), axis(e.to)]).collect();
+        coords.sort_by(|a, b| a.total_cmp(b));
+        let step = ((coords.len() as f32).sqrt() as usize).max(1);
+        let mut bounds: Vec<f32> = coords.iter().cloned().step_by(step).skip(1).collect();
+        bounds.dedup();
+
+        let mut bands = Bands { bands: vec![vec![]; bounds.len() + 1], bounds };
+        for (i, e) in edges.iter().enumerate() {
+            let (a, b) = (axis(e.from), axis(e.to));
+            for band in bands.band(a.min(b)) ..= bands.band(a.max(b)) {
+                bands.bands[band].push(i);
+            }
+        }
+        bands
+    }
+    #[inline]
+    fn band(&self, x: f32) -> usize {
+        self.bounds.partition_point(|&b| b <= x)
+    }
+    // the edges that can contain `x` in their range
+    fn edges(&self, x: f32) -> &[usize] {
+        &self.bands[self.band(x)]
+    }
+}
+
+// the winding number of the edges of `owner` just beside the segment `from` → `to`,
+// on the side the axis `dir` points to. `bands` has to be across `dir`.
+// edges along the segment are ignored, every other edge is either away from it
+// or ends at its endpoints after splitting.
+fn winding(edges: &[Edge], bands: &Bands, owner: usize, from: Vector, to: Vector, dir: Vector) -> i32 {
+    let origin = from.lerp(to, 0.5);
+    let segment = edge_key(from, to);
+    // coordinates along the ray (u) and across it (v)
+    let local = |p: Vector| {
+        let p = p - origin;
+        (p.dot(dir), cross(dir, p))
+    };
+    let across = if dir.x() != 0.0 { origin.y() } else { origin.x() };
+    let mut winding = 0;
+    for e in bands.edges(across).iter().map(|&i| &edges[i]).filter(|e| e.owner == owner) {
+        if edge_key(e.from, e.to) == segment {
+            continue;
+        }
+        let (u0, v0) = local(e.from);
+        let (u1, v1) = local(e.to);
+        // half open, so rays through vertices count once
+        if (v0 <= 0.0) != (v1 <= 0.0) {
+            let u = u0 + (u1 - u0) * (v0 / (v0 - v1));
+            if u > 0.0 {
+                winding += if v1 > v0 { 1 } else { -1 };
+            }
+        }
+    }
+    winding
+}
+
+/// Combine two outlines with the given operation.
+///
+/// `a_fill` and `b_fill` are the fill rules that define the inside of `a` and `b`.
+/// Curves are flattened with an error of at most `tolerance`. The result only contains lines
+/// and does not cover any area twice, so it can be filled with either fill rule.
+pub fn boolean<A: Outline, B: Outline, O: Outline>(op: BooleanOp, a: &A, a_fill: FillRule, b: &B, b_fill: FillRule, tolerance: f32) -> O {
+    let eps = tolerance * 0.01;
+    let mut input = vec![];
+    edges(a, tolerance, eps, 0, &mut input);
+    edges(b, tolerance, eps, 1, &mut input);
+    let edges = split(&input, eps);
+    let (by_x, by_y) = (Bands::new(&edges, Vector::x), Bands::new(&edges, Vector::y));
+
+    // classify each distinct segment once
+    let mut kept: Vec<(Vector, Vector)> = vec![];
+    let mut seen = HashSet::new();
+    for e in &edges {
+        if !seen.insert(edge_key(e.from, e.to)) {
+            continue;
+        }
+        let d = e.to - e.from;
+        // rays along the x axis, unless the segment is horizontal
+        let (dir, bands) = if d.y() != 0.0 { (Vector::new(1.0, 0.0), &by_y) } else { (Vector::new(0.0, 1.0), &by_x) };
+        let inside = |dir: Vector| op.apply(
+            is_inside(winding(&edges, bands, 0, e.from, e.to, dir), a_fill),
+            is_inside(winding(&edges, bands, 1, e.from, e.to, dir), b_fill),
+        );
+        let (left, right) = if cross(d, dir) > 0.0 { (inside(dir), inside(-dir)) } else { (inside(-dir), inside(dir)) };
+        match (left, right) {
+            (true, false) => kept.push((e.from, e.to)),
+            (false, true) => kept.push((e.to, e.from)),
+            _ => {}
+        }
+    }
+
+    // link the edges into contours
+    let mut outgoing: HashMap<Key, Vec<usize>> = HashMap::new();
+    for (i, &(from, _)) in kept.iter().enumerate() {
+        outgoing.entry(key(from)).or_default().push(i);
+    }
+    let mut used = vec![false; kept.len()];
+    let mut builder = PathBuilder::<O>::with_policy(ErrorPolicy::Ignore);
+    for start in 0 .. kept.len() {
+        if used[start] {
+            continue;
+        }
+        let mut points = vec![kept[start].0];
+        let mut current = start;
+        loop {
+            used[current] = true;
+            let p = kept[current].1;
+            let next = outgoing.get(&key(p))
+                .and_then(|list| list.iter().cloned().find(|&i| !used[i]));
+            match next {
+                Some(i) => {
+                    points.push(p);
+                    current = i;
+                }
+                None => break,
+            }
+        }
+
+        // drop points in the middle of straight lines
+        let n = points.len();
+        let points: Vec<Vector> = (0 .. n)
+            .filter(|&i| {
+                let (prev, p, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+                cross(p - prev, next - p) != 0.0 || (p - prev).dot(next - p) < 0.0
+            })
+            .map(|i| points[i])
+            .collect();
+        if points.len() < 3 {
+            continue;
+        }
+        builder.move_to(points[0]);
+        for &p in &points[1 ..] {
+            builder.line_to(p);
+        }
+        builder.close();
+    }
+    builder.into_outline()
+}
@@ -0,0 +1,137 @@
+//! Approximate curves by line segments.
+//!
+//! Curves are split into segments of equal parameter length. The number of segments
+//! only depends on the control points and the tolerance, so the output is deterministic.
+//!
+//! The tolerance has to be positive and finite. A single curve is never split into
+//! more than `MAX_SEGMENTS` lines, so the error bound does not hold for curves that
+//! are enormous compared to the tolerance.
+
+use crate::{Outline, PathBuilder, ErrorPolicy, PathEvent, Vector};
+use crate::geom::{quadratic_point, cubic_point};
+
+/// The largest number of lines a single curve is split into.
+pub const MAX_SEGMENTS: usize = 1 << 16;
+
+/// The number of lines needed to approximate the quadratic curve within `tolerance`.
+///
+/// Uses the bound `|B''| h² / 8` on the distance between a curve and its chord.
+///
+/// # Panics
+/// If `tolerance` is not positive and finite.
+pub fn quadratic_segments(p0: Vector, c: Vector, p1: Vector, tolerance: f32) -> usize {
+    check_tolerance(tolerance);
+    segments(second_difference(p0, c, p1) / (4.0 * tolerance as f64))
+}
+
+/// The number of lines needed to approximate the cubic curve within `tolerance`.
+///
+/// # Panics
+/// If `tolerance` is not positive and finite.
+pub fn cubic_segments(p0: Vector, c1: Vector, c2: Vector, p1: Vector, tolerance: f32) -> usize {
+    check_tolerance(tolerance);
+    // the second derivative is bounded by 6 times the larger second difference
+    let dd = second_difference(p0, c1, c2).max(second_difference(c1, c2, p1));
+    segments(dd * 0.75 / tolerance as f64)
+}
+
+fn check_tolerance(tolerance: f32) {
+    assert!(tolerance > 0.0 && tolerance.is_finite(), "flattening tolerance must be positive and finite, got {}", tolerance);
+}
+
+// the length of `a - 2b + c`, in f64 so large coordinates do not overflow
+fn second_difference(a: Vector, b: Vector, c: Vector) -> f64 {
+    let d = |f: fn(Vector) -> f32| f(a) as f64 - 2.0 * f(b) as f64 + f(c) as f64;
+    d(Vector::x).hypot(d(Vector::y))
+}
+
+fn segments(n_squared: f64) -> usize {
+    let n = n_squared.sqrt().ceil();
+    if n.is_nan() || n < 1.0 {
+        // degenerate curve, or non-finite control points
+        1
+    } else if n >= MAX_SEGMENTS as f64 {
+        MAX_SEGMENTS
+    } else {
+        n as usize
+    }
+}
+
+/// Points on the quadratic curve after `p0`, ending with `p1`.
+pub(crate) fn flatten_quadratic(p0: Vector, c: Vector, p1: Vector, tolerance: f32) -> impl Iterator<Item=Vector> {
+    let n = quadratic_segments(p0, c, p1, tolerance);
+    (1 ..= n).map(move |i| if i == n { p1 } else { quadratic_point(p0, c, p1, i as f32 / n as f32) })
+}
+
+/// Points on the cubic curve after `p0`, ending with `p1`.
+pub(crate) fn flatten_cubic(p0: Vector, c1: Vector, c2: Vector, p1: Vector, tolerance: f32) -> impl Iterator<Item=Vector> {
+    let n = cubic_segments(p0, c1, c2, p1, tolerance);
+    (1 ..= n).map(move |i| if i == n { p1 } else { cubic_point(p0, c1, c2, p1, i as f32 / n as f32) })
+}
+
+/// A flattened contour: its points and whether it is closed.
+pub(crate) struct Polyline {
+    pub points: Vec<Vector>,
+    pub closed: bool,
+}
+
+/// Flatten every contour of `outline` into a polyline.
+pub(crate) fn polylines(outline: &impl Outline, tolerance: f32) -> Vec<Polyline> {
+    check_tolerance(tolerance);
+    let mut lines = vec![];
+    let mut current = Polyline { points: vec![], closed: false };
+    for event in outline.events() {
+        let last = current.points.last().cloned().unwrap_or_default();
+        match event {
+            PathEvent::MoveTo(p) => {
+                if !current.points.is_empty() {
+                    lines.push(current);
+                }
+                current = Polyline { points: vec![p], closed: false };
+            }
+            PathEvent::LineTo(p) => current.points.push(p),
+            PathEvent::QuadTo(c, p) => current.points.extend(flatten_quadratic(last, c, p, tolerance)),
+            PathEvent::CubicTo(c1, c2, p) => current.points.extend(flatten_cubic(last, c1, c2, p, tolerance)),
+            PathEvent::Close => current.closed = true,
+        }
+    }
+    if !current.points.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Replace all curves in `outline` by lines that deviate at most `tolerance` from the curve.
+///
+/// Each quadratic curve becomes `quadratic_segments` lines and each cubic curve
+/// (including arcs) `cubic_segments` lines.
+///
+/// # Panics
+/// If `tolerance` is not positive and finite.
+pub fn flatten<A: Outline, B: Outline>(outline: &A, tolerance: f32) -> B {
+    check_tolerance(tolerance);
+    let mut builder = PathBuilder::<B>::with_policy(ErrorPolicy::Ignore);
+    let mut last = Vector::default();
+    for event in outline.events() {
+        match event {
+            PathEvent::MoveTo(p) => builder.move_to(p),
+            PathEvent::LineTo(p) => builder.line_to(p),
+            PathEvent::QuadTo(c, p) => {
+                for q in flatten_quadratic(last, c, p, tolerance) {
+                    builder.line_to(q);
+                }
+            }
+            PathEvent::CubicTo(c1, c2, p) => {
+                for q in flatten_cubic(last, c1, c2, p, tolerance) {
+                    builder.line_to(q);
+                }
+            }
+            PathEvent::Close => builder.close(),
+        }
+        last = match event {
+            PathEvent::MoveTo(p) | PathEvent::LineTo(p) | PathEvent::QuadTo(_, p) | PathEvent::CubicTo(_, _, p) => p,
+            PathEvent::Close => last,
+        };
+    }
+    builder.into_outline()
+}
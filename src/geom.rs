@@ -0,0 +1,54 @@
+//! Curve math shared by the geometry algorithms.
+
+use crate::Vector;
+
+#[inline]
+pub fn quadratic_point(p0: Vector, c: Vector, p1: Vector, t: f32) -> Vector {
+    let s = 1.0 - t;
+    p0 * (s * s) + c * (2.0 * s * t) + p1 * (t * t)
+}
+#[inline]
+pub fn cubic_point(p0: Vector, c1: Vector, c2: Vector, p1: Vector, t: f32) -> Vector {
+    let s = 1.0 - t;
+    p0 * (s * s * s) + c1 * (3.0 * s * s * t) + c2 * (3.0 * s * t * t) + p1 * (t * t * t)
+}
+
+// real solutions of a t² + b t + c = 0
+pub fn quadratic_roots(a: f32, b: f32, c: f32) -> [Option<f32>; 2] {
+    if a.abs() < 1e-12 {
+        if b == 0.0 {
+            return [None, None];
+        }
+        return [Some(-c / b), None];
+    }
+    let disc = b * b - 4.0 * a * c;
+    if disc < 0.0 {
+        return [None, None];
+    }
+    let sq = disc.sqrt();
+    [Some((-b + sq) / (2.0 * a)), Some((-b - sq) / (2.0 * a))]
+}
+
+// the blossom of the quadratic curve: the control points of the part between t0 and t1
+// are (f(t0, t0), f(t0, t1), f(t1, t1))
+pub fn quadratic_blossom(p0: Vector, c: Vector, p1: Vector, a: f32, b: f32) -> Vector {
+    let (q0, q1) = (p0.lerp(c, a), c.lerp(p1, a));
+    q0.lerp(q1, b)
+}
+// the blossom of the cubic curve: the control points of the part between t0 and t1
+// are (f(t0, t0, t0), f(t0, t0, t1), f(t0, t1, t1), f(t1, t1, t1))
+pub fn cubic_blossom(p0: Vector, c1: Vector, c2: Vector, p1: Vector, a: f32, b: f32, c: f32) -> Vector {
+    let (q0, q1, q2) = (p0.lerp(c1, a), c1.lerp(c2, a), c2.lerp(p1, a));
+    let (r0, r1) = (q0.lerp(q1, b), q1.lerp(q2, b));
+    r0.lerp(r1, c)
+}
+
+#[inline]
+pub fn quadratic_derivative(p0: Vector, c: Vector, p1: Vector, t: f32) -> Vector {
+    ((c - p0) * (1.0 - t) + (p1 - c) * t) * 2.0
+}
+#[inline]
+pub fn cubic_derivative(p0: Vector, c1: Vector, c2: Vector, p1: Vector, t: f32) -> Vector {
+    let s = 1.0 - t;
+    ((c1 - p0) * (s * s) + (c2 - c1) * (2.0 * s * t) + (p1 - c2) * (t * t)) * 3.0
+}
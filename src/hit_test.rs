@@ -0,0 +1,64 @@
+//! Test whether a point is covered by the fill or the stroke of an outline.
+
+use crate::{Outline, FillRule, LineStyle, LineJoin, Vector, Rect, Path};
+use crate::flatten::polylines;
+use crate::stroke::stroke_to_fill;
+
+// whether `p` is within `margin` of the rectangle
+#[inline]
+fn near(rect: Rect, p: Vector, margin: f32) -> bool {
+    p.x() >= rect.min_x() - margin && p.x() <= rect.max_x() + margin &&
+    p.y() >= rect.min_y() - margin && p.y() <= rect.max_y() + margin
+}
+
+/// The winding number of `outline` around `p`, with curves flattened to within `tolerance`.
+///
+/// Every contour counts as closed.
+pub fn winding_number(outline: &impl Outline, p: Vector, tolerance: f32) -> i32 {
+    let mut winding = 0;
+    for line in polylines(outline, tolerance) {
+        let points = &line.points;
+        let closing = Some((points[points.len() - 1], points[0]));
+        for (a, b) in points.windows(2).map(|w| (w[0], w[1])).chain(closing) {
+            // cast a ray to the right. half open, so vertices on the ray count once.
+            if (a.y() <= p.y()) != (b.y() <= p.y()) {
+                let x = a.x() + (p.y() - a.y()) * (b.x() - a.x()) / (b.y() - a.y());
+                if x > p.x() {
+                    winding += if b.y() > a.y() { 1 } else { -1 };
+                }
+            }
+        }
+    }
+    winding
+}
+
+/// Whether `p` is inside of `outline` when filled with `fill_rule`.
+pub fn contains(outline: &impl Outline, p: Vector, fill_rule: FillRule, tolerance: f32) -> bool {
+    match outline.bounding_box() {
+        Some(rect) if near(rect, p, 0.0) => {}
+        _ => return false
+    }
+    let winding = winding_number(outline, p, tolerance);
+    match fill_rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+/// Whether `p` is covered by the stroke of `outline` with the given `style`.
+///
+/// Caps, joins and dashes are taken into account.
+pub fn stroke_contains(outline: &impl Outline, p: Vector, style: &LineStyle, tolerance: f32) -> bool {
+    // the furthest a stroke can reach beyond the outline, at square caps or miters
+    let half_width = 0.5 * style.width;
+    let reach = match style.join {
+        LineJoin::Miter(limit) => half_width * limit.max(std::f32::consts::SQRT_2),
+        _ => half_width * std::f32::consts::SQRT_2,
+    };
+    match outline.bounding_box() {
+        Some(rect) if near(rect, p, reach) => {}
+        _ => return false
+    }
+    let fill: Path = stroke_to_fill(outline, style, tolerance);
+    winding_number(&fill, p, tolerance) != 0
+}
@@ -1,24 +1,29 @@
 use pathfinder_content::{
     outline::{Contour as PaContour, Outline as PaOutline, ArcDirection},
     stroke::{StrokeStyle, OutlineStrokeToFill},
+    dash::OutlineDash,
     fill::FillRule as PaFillRule,
-    effects::BlendMode
+    effects::BlendMode as PaBlendMode
 };
 use pathfinder_renderer::{
-    scene::{Scene, DrawPath, ClipPath as PaClipPath, ClipPathId},
+    scene::{Scene, DrawPath, ClipPath as PaClipPath, ClipPathId, RenderTarget, RenderTargetId},
     paint::{Paint as PaPaint, PaintId as PaPaintId},
 };
 use pathfinder_color::ColorU;
 use pathfinder_content::{
-    gradient::{Gradient},
+    gradient::{Gradient, GradientWrap},
     pattern::{Pattern, Image, PatternSource, PatternFlags},
     stroke::{LineJoin as PaLineJoin, LineCap as PaLineCap}
 };
 use pathfinder_geometry::{
     rect::RectF,
-    vector::Vector2I
+    vector::Vector2I,
+    line_segment::LineSegment2F,
+};
+use crate::{
+    Contour, Vector, Surface, Outline, Transform, Paint, PathStyle, FillRule, PixelFormat, LineCap, LineJoin,
+    Rgba8, GradientStop, Spread, BlendMode, Layer, PathEvent, TextureError, pixels
 };
-use crate::{Contour, Vector, Surface, Outline, Transform, Paint, PathStyle, FillRule, PixelFormat, LineCap, LineJoin};
 use std::sync::Arc;
 
 impl Contour for PaContour {
@@ -104,23 +109,94 @@ impl Outline for PaOutline {
     fn clear(&mut self) {
         PaOutline::clear(self)
     }
+    fn events<'a>(&'a self) -> Box<dyn Iterator<Item=PathEvent> + 'a> {
+        Box::new(self.contours().iter().flat_map(contour_events))
+    }
+}
+
+// walks the points of the contour. control points are followed by the endpoint of their segment.
+// in a closed contour, control points at the end belong to a curve back to the first point.
+fn contour_events<'a>(contour: &'a PaContour) -> impl Iterator<Item=PathEvent> + 'a {
+    let n = contour.len() as u32;
+    let closed = contour.is_closed();
+    let mut i = 0;
+    let mut pending_close = n > 0 && closed;
+    std::iter::from_fn(move || {
+        let p = |i| contour.position_of(if i == n { 0 } else { i });
+        let endpoint = |i| (i < n && contour.point_is_endpoint(i)) || (i == n && closed);
+        let (event, len) = match i {
+            _ if i >= n => {
+                if !pending_close {
+                    return None;
+                }
+                pending_close = false;
+                return Some(PathEvent::Close);
+            }
+            0 => (PathEvent::MoveTo(p(0)), 1),
+            _ if endpoint(i) => (PathEvent::LineTo(p(i)), 1),
+            _ if endpoint(i + 1) => (PathEvent::QuadTo(p(i), p(i + 1)), 2),
+            _ if endpoint(i + 2) => (PathEvent::CubicTo(p(i), p(i + 1), p(i + 2)), 3),
+            _ => {
+                warn!("contour ends with control points");
+                i = n;
+                pending_close = false;
+                return None;
+            }
+        };
+        i += len;
+        Some(event)
+    })
+}
+
+#[derive(Clone)]
+pub struct Dash {
+    array: Vec<f32>,
+    offset: f32,
 }
 
 #[derive(Clone)]
 pub struct Style {
     fill: Option<PaPaintId>,
-    stroke: Option<(PaPaintId, StrokeStyle)>,
+    stroke: Option<(PaPaintId, StrokeStyle, Option<Dash>)>,
     fill_rule: PaFillRule,
 }
 #[inline]
+fn color((r, g, b, a): Rgba8) -> ColorU {
+    ColorU { r, g, b, a }
+}
+// pathfinder only knows how to clamp or repeat a gradient.
+// Spread::Reflect is emulated with `reflect_as_repeat` before getting here.
+fn gradient(mut gradient: Gradient, stops: &[GradientStop], spread: Spread) -> PaPaint {
+    for stop in stops {
+        gradient.add_color_stop(color(stop.color), stop.offset);
+    }
+    gradient.wrap = match spread {
+        Spread::Pad => GradientWrap::Clamp,
+        Spread::Repeat | Spread::Reflect => GradientWrap::Repeat,
+    };
+    PaPaint::from_gradient(gradient)
+}
+#[inline]
 fn paint(paint: Paint<Scene>) -> PaPaint {
     match paint {
-        Paint::Solid((r, g, b, a)) => PaPaint::from_color(ColorU { r, g, b, a }),
+        Paint::Solid(c) => PaPaint::from_color(color(c)),
         Paint::Image(image, tr) => {
             let mut pattern = Pattern::from_image(image);
             pattern.apply_transform(tr);
             PaPaint::from_pattern(pattern)
         }
+        Paint::LinearGradient(g) => {
+            let g = g.reflect_as_repeat();
+            let line = LineSegment2F::new(g.start, g.end);
+            gradient(Gradient::linear(line), &g.stops, g.spread)
+        }
+        Paint::RadialGradient(g) => {
+            // pathfinder interpolates between two circles, starting with radius 0 in the focus.
+            let g = g.reflect_as_repeat();
+            let line = LineSegment2F::new(g.focus, g.center);
+            let radii = Vector::new(0.0, g.radius);
+            gradient(Gradient::radial(line, radii.0), &g.stops, g.spread)
+        }
     }
 }
 impl Into<PaFillRule> for FillRule {
@@ -152,6 +228,40 @@ impl Into<PaLineJoin> for LineJoin {
         }
     }
 }
+impl Into<PaBlendMode> for BlendMode {
+    #[inline]
+    fn into(self) -> PaBlendMode {
+        match self {
+            BlendMode::Normal => PaBlendMode::SrcOver,
+            BlendMode::Multiply => PaBlendMode::Multiply,
+            BlendMode::Screen => PaBlendMode::Screen,
+            BlendMode::Overlay => PaBlendMode::Overlay,
+            BlendMode::Darken => PaBlendMode::Darken,
+            BlendMode::Lighten => PaBlendMode::Lighten,
+            BlendMode::ColorDodge => PaBlendMode::ColorDodge,
+            BlendMode::ColorBurn => PaBlendMode::ColorBurn,
+            BlendMode::HardLight => PaBlendMode::HardLight,
+            BlendMode::SoftLight => PaBlendMode::SoftLight,
+            BlendMode::Difference => PaBlendMode::Difference,
+            BlendMode::Exclusion => PaBlendMode::Exclusion,
+            BlendMode::Hue => PaBlendMode::Hue,
+            BlendMode::Saturation => PaBlendMode::Saturation,
+            BlendMode::Color => PaBlendMode::Color,
+            BlendMode::Luminosity => PaBlendMode::Luminosity,
+        }
+    }
+}
+
+// a layer is drawn into its own render target,
+// which is then used as a pattern to fill the view box when the layer is popped.
+struct LayerState {
+    render_target: RenderTargetId,
+    size: Vector2I,
+    opacity: f32,
+    blend_mode: PaBlendMode,
+    clip: Option<ClipPathId>,
+}
+
 impl Surface for Scene {
     type Outline = PaOutline;
     type Style = Style;
@@ -173,14 +283,29 @@ impl Surface for Scene {
                     line_width: line.width,
                     line_cap: line.cap.into(),
                     line_join: line.join.into(),
+                },
+                if line.is_dashed() {
+                    Some(Dash { array: line.dash_array.to_vec(), offset: line.dash_offset })
+                } else {
+                    None
                 }
             )),
             fill_rule: style.fill_rule.into()
         }
     }
     fn draw_path(&mut self, path: Self::Outline, style: &Self::Style, clip: Option<&Self::ClipPath>) {
-        let stroke = style.stroke.map(|(paint, stroke_style)| {
-            let mut stroke_to_fill = OutlineStrokeToFill::new(&path, stroke_style);
+        let stroke = style.stroke.as_ref().map(|&(paint, stroke_style, ref dash)| {
+            let dashed;
+            let outline = match *dash {
+                Some(ref dash) => {
+                    let mut outline_dash = OutlineDash::new(&path, &dash.array, dash.offset);
+                    outline_dash.dash();
+                    dashed = outline_dash.into_outline();
+                    &dashed
+                }
+                None => &path
+            };
+            let mut stroke_to_fill = OutlineStrokeToFill::new(outline, stroke_style);
             stroke_to_fill.offset();
             let outline = stroke_to_fill.into_outline();
             let mut draw_path = DrawPath::new(outline, paint);
@@ -202,13 +327,44 @@ impl Surface for Scene {
         clip_path.set_fill_rule(fill_rule.into());
         self.push_clip_path(clip_path)
     }
-    fn texture(&mut self, width: u32, height: u32, data: &[u8], format: PixelFormat) -> Self::Image {
-        let data: Vec<ColorU> = match format {
-            PixelFormat::L8 => data.iter().map(|&l| ColorU { r: l, g: l, b: l, a: 255 }).collect(),
-            PixelFormat::Rgb24 => data.chunks(3).map(|c| ColorU { r: c[0], g: c[1], b: c[2], a: 255 }).collect(),
-            PixelFormat::Rgba32 => data.chunks(4).map(|c| ColorU { r: c[0], g: c[1], b: c[2], a: c[3] }).collect(),
+    fn texture_with_stride(&mut self, width: u32, height: u32, data: &[u8], stride: usize, format: PixelFormat)
+        -> Result<Self::Image, TextureError>
+    {
+        // validate before allocating. a valid buffer holds at least one byte per pixel.
+        let rows = pixels::rows(width, height, data, stride, format)?;
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for row in rows {
+            pixels.extend(pixels::rgba8(row, format).map(|(r, g, b, a)| ColorU { r, g, b, a }));
+        }
+        Ok(Image::new(Vector2I::new(width as i32, height as i32), Arc::new(pixels)))
+    }
+    fn push_layer(&mut self, opacity: f32, blend_mode: BlendMode, clip: Option<&Self::ClipPath>) -> Layer {
+        let size = self.view_box().size().ceil().to_i32();
+        let render_target = self.push_render_target(RenderTarget::new(size, String::new()));
+        Layer::new(LayerState {
+            render_target,
+            size,
+            opacity,
+            blend_mode: blend_mode.into(),
+            clip: clip.cloned(),
+        })
+    }
+    fn pop_layer(&mut self, layer: Layer) {
+        let layer: LayerState = match layer.into_state() {
+            Some(layer) => layer,
+            None => return
         };
-        assert_eq!(data.len(), width as usize * height as usize);
-        Image::new(Vector2I::new(width as i32, height as i32), Arc::new(data))
+        self.pop_render_target();
+
+        let pattern = Pattern::from_render_target(layer.render_target, layer.size);
+        let mut paint = PaPaint::from_pattern(pattern);
+        let alpha = (layer.opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+        paint.set_base_color(ColorU { r: 255, g: 255, b: 255, a: alpha });
+        let paint = self.push_paint(&paint);
+
+        let mut draw_path = DrawPath::new(PaOutline::from_rect(self.view_box()), paint);
+        draw_path.set_blend_mode(layer.blend_mode);
+        draw_path.set_clip_path(layer.clip);
+        self.push_path(draw_path);
     }
 }
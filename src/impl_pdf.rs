@@ -0,0 +1,443 @@
+//! A `Surface` that writes a single page PDF document.
+//!
+//! Paths, clips and solid colors map directly to content stream operators.
+//! Alpha and blend modes are set through ExtGState dictionaries, layers become transparency groups.
+//! Images are image XObjects (with a soft mask for alpha) painted by tiling patterns,
+//! gradients are shading patterns. PDF has no equivalent for the alpha of gradient stops
+//! or the repeat and reflect spread modes, so stops are opaque and gradients are always padded.
+//!
+//! PDF coordinates have the origin in the bottom left corner, so all y coordinates are flipped.
+//! PDF numbers can not be NaN or infinite, those are written as 0.
+
+use std::fmt::{self, Write};
+use crate::{Surface, Vector, Transform, Rgba8, PathStyle, FillRule, PixelFormat, Paint, GradientStop, BlendMode, Layer,
+    LineCap, LineJoin, TextureError, Path, convert_pixels};
+use crate::path::Segment;
+use itertools::Itertools;
+
+// objects with a fixed number. everything else is numbered from FIRST_OBJECT on.
+const CATALOG: usize = 1;
+const PAGES: usize = 2;
+const PAGE: usize = 3;
+const RESOURCES: usize = 4;
+const CONTENT: usize = 5;
+const FIRST_OBJECT: usize = 6;
+
+// an open layer
+struct Group {
+    content: String,
+    opacity: f32,
+    blend_mode: BlendMode,
+    clip: Option<String>,
+}
+
+pub struct Pdf {
+    size: Vector,
+    page: String,
+    layers: Vec<Group>,
+    // bodies of the objects from FIRST_OBJECT on
+    objects: Vec<Vec<u8>>,
+    // contents of the dictionaries named /GS0, /GS1, …
+    ext_g_states: Vec<String>,
+    // object numbers of /P0, /P1, …
+    patterns: Vec<usize>,
+    // object numbers of /Fm0, /Fm1, …
+    forms: Vec<usize>,
+}
+impl Pdf {
+    /// Close all open layers and return the PDF file.
+    pub fn finish(mut self) -> Vec<u8> {
+        while !self.layers.is_empty() {
+            self.pop_layer(Layer::empty());
+        }
+
+        let mut resources = String::from("<< ");
+        if !self.ext_g_states.is_empty() {
+            write!(resources, "/ExtGState << {} >> ",
+                self.ext_g_states.iter().enumerate().format_with(" ", |(i, gs), f| f(&format_args!("/GS{} << {} >>", i, gs)))
+            ).unwrap();
+        }
+        if !self.patterns.is_empty() {
+            write!(resources, "/Pattern << {} >> ",
+                self.patterns.iter().enumerate().format_with(" ", |(i, n), f| f(&format_args!("/P{} {} 0 R", i, n)))
+            ).unwrap();
+        }
+        if !self.forms.is_empty() {
+            write!(resources, "/XObject << {} >> ",
+                self.forms.iter().enumerate().format_with(" ", |(i, n), f| f(&format_args!("/Fm{} {} 0 R", i, n)))
+            ).unwrap();
+        }
+        resources.push_str(">>");
+
+        let fixed = [
+            format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES).into_bytes(),
+            format!("<< /Type /Pages /Kids [{} 0 R] /Count 1 >>", PAGE).into_bytes(),
+            format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources {} 0 R /Contents {} 0 R /Group << /S /Transparency /CS /DeviceRGB >> >>",
+                PAGES, Num(self.size.x()), Num(self.size.y()), RESOURCES, CONTENT
+            ).into_bytes(),
+            resources.into_bytes(),
+            stream("", self.page.as_bytes()),
+        ];
+
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(fixed.len() + self.objects.len());
+        for (i, body) in fixed.iter().chain(&self.objects).enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = out.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+        for offset in offsets.iter() {
+            // every entry is exactly 20 bytes long
+            writeln!(trailer, "{:010} 00000 n ", offset).unwrap();
+        }
+        write!(trailer, "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n", offsets.len() + 1, CATALOG, xref).unwrap();
+        out.extend_from_slice(trailer.as_bytes());
+        out
+    }
+
+    fn add_object(&mut self, body: Vec<u8>) -> usize {
+        self.objects.push(body);
+        FIRST_OBJECT + self.objects.len() - 1
+    }
+    fn add_pattern(&mut self, body: Vec<u8>) -> usize {
+        let object = self.add_object(body);
+        self.patterns.push(object);
+        self.patterns.len() - 1
+    }
+    // the name index of an ExtGState with the given entries, reusing an existing one if possible
+    fn ext_g_state(&mut self, entries: String) -> usize {
+        match self.ext_g_states.iter().position(|gs| *gs == entries) {
+            Some(i) => i,
+            None => {
+                self.ext_g_states.push(entries);
+                self.ext_g_states.len() - 1
+            }
+        }
+    }
+    // the content stream currently drawn to
+    fn content(&mut self) -> &mut String {
+        match self.layers.last_mut() {
+            Some(group) => &mut group.content,
+            None => &mut self.page
+        }
+    }
+    // maps our coordinates (origin top left, y down) to the PDF ones
+    fn flip(&self) -> Transform {
+        Transform::row_major(1.0, 0.0, 0.0, -1.0, 0.0, self.size.y())
+    }
+
+    // the operators that select `paint` and its alpha, or None if nothing would be visible
+    fn paint(&mut self, paint: Paint<Self>, stroke: bool) -> Option<(String, u8)> {
+        let (color_op, space_op, pattern_op) = if stroke { ("RG", "CS", "SCN") } else { ("rg", "cs", "scn") };
+        let pattern = match paint {
+            Paint::Solid((_, _, _, 0)) => return None,
+            Paint::Solid((r, g, b, a)) => return Some((format!("{} {} {} {}", f(r), f(g), f(b), color_op), a)),
+            Paint::LinearGradient(ref g) if g.stops.is_empty() => return None,
+            Paint::RadialGradient(ref g) if g.stops.is_empty() => return None,
+            Paint::LinearGradient(g) => self.add_pattern(dict_object(&format!(
+                "/Type /Pattern /PatternType 2 /Matrix {} /Shading << /ShadingType 2 /ColorSpace /DeviceRGB /Coords [{} {} {} {}] /Function {} /Extend [true true] >>",
+                matrix(self.flip()), Num(g.start.x()), Num(g.start.y()), Num(g.end.x()), Num(g.end.y()), function(&g.stops)
+            ))),
+            Paint::RadialGradient(g) => self.add_pattern(dict_object(&format!(
+                "/Type /Pattern /PatternType 2 /Matrix {} /Shading << /ShadingType 3 /ColorSpace /DeviceRGB /Coords [{} {} 0 {} {} {}] /Function {} /Extend [true true] >>",
+                matrix(self.flip()), Num(g.focus.x()), Num(g.focus.y()), Num(g.center.x()), Num(g.center.y()), Num(g.radius), function(&g.stops)
+            ))),
+            Paint::Image(image, tr) => {
+                // one tile shows the image with the first row at the top
+                let (w, h) = (image.width, image.height);
+                let dict = format!(
+                    "/Type /Pattern /PatternType 1 /PaintType 1 /TilingType 1 /BBox [0 0 {w} {h}] /XStep {w} /YStep {h} /Matrix {} /Resources << /XObject << /Im0 {} 0 R >> >>",
+                    matrix(self.flip() * tr), image.id, w = w, h = h
+                );
+                let content = format!("q {w} 0 0 -{h} 0 {h} cm /Im0 Do Q", w = w, h = h);
+                self.add_pattern(stream(&dict, content.as_bytes()))
+            }
+        };
+        Some((format!("/Pattern {} /P{} {}", space_op, pattern, pattern_op), 255))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Image {
+    // object number of the image XObject
+    id: usize,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Style {
+    // operators that set up colors, alpha and line style
+    setup: String,
+    // the painting operator, None if the style does not paint anything
+    op: Option<&'static str>,
+}
+
+fn f(u: u8) -> f32 { u as f32 / 255. }
+
+// a number in PDF syntax
+#[derive(Copy, Clone)]
+struct Num(f32);
+impl fmt::Display for Num {
+    fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
+        let v = if self.0.is_finite() { self.0 } else { 0.0 };
+        write!(out, "{}", v)
+    }
+}
+
+// an indirect stream object with the given dictionary entries
+fn stream(dict: &str, data: &[u8]) -> Vec<u8> {
+    let mut out = format!("<< {} /Length {} >>\nstream\n", dict, data.len()).into_bytes();
+    out.extend_from_slice(data);
+    out.extend_from_slice(b"\nendstream");
+    out
+}
+// a plain dictionary object
+fn dict_object(dict: &str) -> Vec<u8> {
+    format!("<< {} >>", dict).into_bytes()
+}
+
+fn matrix(tr: Transform) -> String {
+    format!("[{} {} {} {} {} {}]", Num(tr.m11()), Num(tr.m21()), Num(tr.m12()), Num(tr.m22()), Num(tr.m31()), Num(tr.m32()))
+}
+
+// a function from 0 to 1 that interpolates between the stops
+fn function(stops: &[GradientStop]) -> String {
+    let color = |(r, g, b, _): Rgba8| format!("[{} {} {}]", f(r), f(g), f(b));
+
+    // offsets have to be increasing and cover the whole range
+    let mut offset = 0.0f32;
+    let mut stops: Vec<GradientStop> = stops.iter()
+        .map(|s| {
+            offset = s.offset.max(offset).min(1.0);
+            GradientStop::new(offset, s.color)
+        })
+        .collect();
+    let (first, last) = (stops[0], stops[stops.len() - 1]);
+    stops.insert(0, GradientStop::new(0.0, first.color));
+    stops.push(GradientStop::new(1.0, last.color));
+
+    let functions = stops.windows(2).format_with(" ", |w, f| f(&format_args!(
+        "<< /FunctionType 2 /Domain [0 1] /C0 {} /C1 {} /N 1 >>", color(w[0].color), color(w[1].color)
+    )));
+    let bounds = stops[1 .. stops.len() - 1].iter().map(|s| Num(s.offset)).format(" ");
+    let encode = (1 .. stops.len()).map(|_| "0 1").format(" ");
+    format!("<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{}] /Encode [{}] >>", functions, bounds, encode)
+}
+
+fn write_path(out: &mut String, path: &Path, height: f32) {
+    let point = |p: Vector| format!("{} {}", Num(p.x()), Num(height - p.y()));
+    for sub in path.contours() {
+        writeln!(out, "{} m", point(sub.start)).unwrap();
+        let mut last = sub.start;
+        for &segment in &sub.segments {
+            match segment {
+                Segment::Line(p) => writeln!(out, "{} l", point(p)),
+                // PDF only has cubic curves
+                Segment::Quadratic(c, p) => writeln!(out, "{} {} {} c",
+                    point(last + (c - last) * (2.0 / 3.0)), point(p + (c - p) * (2.0 / 3.0)), point(p)
+                ),
+                Segment::Cubic(c1, c2, p) => writeln!(out, "{} {} {} c", point(c1), point(c2), point(p)),
+            }.unwrap();
+            last = segment.end();
+        }
+        if sub.closed {
+            writeln!(out, "h").unwrap();
+        }
+    }
+}
+
+fn blend_mode_str(m: BlendMode) -> &'static str {
+    match m {
+        BlendMode::Normal => "Normal",
+        BlendMode::Multiply => "Multiply",
+        BlendMode::Screen => "Screen",
+        BlendMode::Overlay => "Overlay",
+        BlendMode::Darken => "Darken",
+        BlendMode::Lighten => "Lighten",
+        BlendMode::ColorDodge => "ColorDodge",
+        BlendMode::ColorBurn => "ColorBurn",
+        BlendMode::HardLight => "HardLight",
+        BlendMode::SoftLight => "SoftLight",
+        BlendMode::Difference => "Difference",
+        BlendMode::Exclusion => "Exclusion",
+        BlendMode::Hue => "Hue",
+        BlendMode::Saturation => "Saturation",
+        BlendMode::Color => "Color",
+        BlendMode::Luminosity => "Luminosity",
+    }
+}
+
+impl Surface for Pdf {
+    type Outline = Path;
+    type Style = Style;
+    // the operators that set the clip
+    type ClipPath = String;
+    type Image = Image;
+
+    fn new(size: Vector) -> Self {
+        Pdf {
+            size,
+            page: String::with_capacity(1024),
+            layers: vec![],
+            objects: vec![],
+            ext_g_states: vec![],
+            patterns: vec![],
+            forms: vec![],
+        }
+    }
+    fn build_style(&mut self, style: PathStyle<Self>) -> Self::Style {
+        let mut setup = String::new();
+        let mut alpha = String::new();
+
+        let fill = style.fill.and_then(|paint| self.paint(paint, false));
+        if let Some((ref ops, a)) = fill {
+            writeln!(setup, "{}", ops).unwrap();
+            if a != 255 {
+                write!(alpha, "/ca {} ", f(a)).unwrap();
+            }
+        }
+
+        // a width of 0 is the thinnest line the device can show in PDF
+        let stroke = style.stroke.filter(|(_, line)| line.width > 0.0)
+            .and_then(|(paint, line)| self.paint(paint, true).map(|p| (p, line)));
+        if let Some(((ref ops, a), ref line)) = stroke {
+            writeln!(setup, "{}", ops).unwrap();
+            if a != 255 {
+                write!(alpha, "/CA {} ", f(a)).unwrap();
+            }
+            let cap = match line.cap {
+                LineCap::Butt => 0,
+                LineCap::Round => 1,
+                LineCap::Square => 2,
+            };
+            let join = match line.join {
+                LineJoin::Miter(_) => 0,
+                LineJoin::Round => 1,
+                LineJoin::Bevel => 2,
+            };
+            write!(setup, "{} w {} J {} j", Num(line.width), cap, join).unwrap();
+            if let LineJoin::Miter(limit) = line.join {
+                write!(setup, " {} M", Num(limit.max(1.0))).unwrap();
+            }
+            if line.is_dashed() {
+                write!(setup, " [{}] {} d", line.dash_array.iter().map(|&d| Num(d)).format(" "), Num(line.dash_offset)).unwrap();
+            }
+            writeln!(setup).unwrap();
+        }
+
+        if !alpha.is_empty() {
+            let gs = self.ext_g_state(alpha.trim_end().into());
+            writeln!(setup, "/GS{} gs", gs).unwrap();
+        }
+
+        let op = match (fill.is_some(), stroke.is_some(), style.fill_rule) {
+            (true, true, FillRule::NonZero) => Some("B"),
+            (true, true, FillRule::EvenOdd) => Some("B*"),
+            (true, false, FillRule::NonZero) => Some("f"),
+            (true, false, FillRule::EvenOdd) => Some("f*"),
+            (false, true, _) => Some("S"),
+            (false, false, _) => None,
+        };
+        Style { setup, op }
+    }
+    fn draw_path(&mut self, path: Self::Outline, style: &Self::Style, clip: Option<&Self::ClipPath>) {
+        let op = match style.op {
+            Some(op) => op,
+            None => return
+        };
+        let height = self.size.y();
+        let out = self.content();
+        writeln!(out, "q").unwrap();
+        if let Some(clip) = clip {
+            out.push_str(clip);
+        }
+        out.push_str(&style.setup);
+        write_path(out, &path, height);
+        writeln!(out, "{}\nQ", op).unwrap();
+    }
+    fn clip_path(&mut self, path: Self::Outline, fill_rule: FillRule) -> Self::ClipPath {
+        let mut ops = String::new();
+        write_path(&mut ops, &path, self.size.y());
+        match fill_rule {
+            FillRule::NonZero => writeln!(ops, "W n"),
+            FillRule::EvenOdd => writeln!(ops, "W* n"),
+        }.unwrap();
+        ops
+    }
+    fn texture_with_stride(&mut self, width: u32, height: u32, data: &[u8], stride: usize, format: PixelFormat)
+        -> Result<Self::Image, TextureError>
+    {
+        let rgba = convert_pixels(width, height, data, stride, format)?;
+        let (channels, color_space) = match format {
+            PixelFormat::L8 | PixelFormat::La16 | PixelFormat::L16 | PixelFormat::La32 => (1, "DeviceGray"),
+            _ => (3, "DeviceRGB")
+        };
+        let mut color = Vec::with_capacity(rgba.len() / 4 * channels);
+        let mut alpha = Vec::with_capacity(rgba.len() / 4);
+        for pixel in rgba.chunks(4) {
+            color.extend_from_slice(&pixel[.. channels]);
+            alpha.push(pixel[3]);
+        }
+
+        let header = |color_space: &str| format!(
+            "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /{} /BitsPerComponent 8",
+            width, height, color_space
+        );
+        let mut dict = header(color_space);
+        if alpha.iter().any(|&a| a != 255) {
+            let mask = self.add_object(stream(&header("DeviceGray"), &alpha));
+            write!(dict, " /SMask {} 0 R", mask).unwrap();
+        }
+        let id = self.add_object(stream(&dict, &color));
+        Ok(Image { id, width, height })
+    }
+    fn push_layer(&mut self, opacity: f32, blend_mode: BlendMode, clip: Option<&Self::ClipPath>) -> Layer {
+        self.layers.push(Group {
+            content: String::new(),
+            opacity,
+            blend_mode,
+            clip: clip.cloned(),
+        });
+        Layer::empty()
+    }
+    fn pop_layer(&mut self, _layer: Layer) {
+        let group = match self.layers.pop() {
+            Some(group) => group,
+            None => return
+        };
+        let form = self.add_object(stream(
+            &format!(
+                "/Type /XObject /Subtype /Form /BBox [0 0 {} {}] /Group << /S /Transparency >> /Resources {} 0 R",
+                Num(self.size.x()), Num(self.size.y()), RESOURCES
+            ),
+            group.content.as_bytes()
+        ));
+        self.forms.push(form);
+        let name = self.forms.len() - 1;
+
+        let mut gs = String::new();
+        if group.opacity != 1.0 {
+            write!(gs, "/ca {0} /CA {0} ", Num(group.opacity.clamp(0.0, 1.0))).unwrap();
+        }
+        if group.blend_mode != BlendMode::Normal {
+            write!(gs, "/BM /{}", blend_mode_str(group.blend_mode)).unwrap();
+        }
+        let gs = if gs.is_empty() { None } else { Some(self.ext_g_state(gs.trim_end().into())) };
+
+        let out = self.content();
+        writeln!(out, "q").unwrap();
+        if let Some(clip) = group.clip {
+            out.push_str(&clip);
+        }
+        if let Some(gs) = gs {
+            writeln!(out, "/GS{} gs", gs).unwrap();
+        }
+        writeln!(out, "/Fm{} Do\nQ", name).unwrap();
+    }
+}
@@ -1,9 +1,25 @@
-use crate::{Contour, Outline, Surface, Vector, Transform, Rgba8, PathStyle, PixelFormat};
-use raqote::{Point, Path, Winding, PathOp, DrawTarget, Source, SolidSource, StrokeStyle, DrawOptions};
+use crate::{
+    Contour, Outline, Surface, Vector, Transform, Rect, Rgba8, PathStyle, PixelFormat, Paint, GradientStop, Spread,
+    FillRule, LineCap, LineJoin, LineStyle, BlendMode, Layer, PathEvent, TextureError, RasterOutput, arc_to_cubics, pixels
+};
+use raqote::{
+    Point, Path, Winding, PathOp, DrawTarget, Source, SolidSource, StrokeStyle, DrawOptions,
+    Gradient, GradientStop as RaGradientStop, Color, Spread as RaSpread,
+    LineCap as RaLineCap, LineJoin as RaLineJoin, ExtendMode, FilterMode,
+    Image as RaImage, Transform as RaTransform, BlendMode as RaBlendMode
+};
+use std::sync::Arc;
 
 fn point(v: Vector) -> Point {
     Point::new(v.x(), v.y())
 }
+fn vector(p: Point) -> Vector {
+    Vector::new(p.x, p.y)
+}
+// euclid multiplies row vectors, so the off-diagonal elements are swapped
+fn transform(tr: Transform) -> RaTransform {
+    RaTransform::row_major(tr.m11(), tr.m21(), tr.m12(), tr.m22(), tr.m31(), tr.m32())
+}
 
 impl Contour for Path {
     fn new() -> Self {
@@ -25,7 +41,16 @@ impl Contour for Path {
         self.ops.push(PathOp::CubicTo(point(c1), point(c2), point(p)));
     }
     fn arc(&mut self, transform: Transform, start_angle: f32, end_angle: f32, clockwise: bool) {
-        self.ops.push(PathOp::)
+        let (start, curves) = arc_to_cubics(transform, start_angle, end_angle, clockwise);
+
+        // like pathfinder, connect the arc to the current point
+        match self.ops.last() {
+            None | Some(&PathOp::Close) => self.ops.push(PathOp::MoveTo(point(start))),
+            _ => self.ops.push(PathOp::LineTo(point(start)))
+        }
+        for (c1, c2, p) in curves {
+            self.ops.push(PathOp::CubicTo(point(c1), point(c2), point(p)));
+        }
     }
     fn close(&mut self) {
         match self.ops.last() {
@@ -43,15 +68,28 @@ impl Contour for Path {
 
 impl Outline for Path {
     type Contour = Path;
-    
+
     fn empty() -> Self {
         Path {
             ops: vec![],
             winding: Winding::EvenOdd
         }
     }
+    // the bounds of all points, including control points
     fn bounding_box(&self) -> Option<Rect> {
-        None // FIXME
+        let mut points = self.ops.iter().flat_map(|op| {
+            let (a, b, c) = match *op {
+                PathOp::MoveTo(p) | PathOp::LineTo(p) => (Some(p), None, None),
+                PathOp::QuadTo(c, p) => (Some(c), Some(p), None),
+                PathOp::CubicTo(c1, c2, p) => (Some(c1), Some(c2), Some(p)),
+                PathOp::Close => (None, None, None)
+            };
+            a.into_iter().chain(b).chain(c)
+        }).map(vector);
+
+        let first = points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+        Some(Rect::from_points(min, max))
     }
     fn add_contour(&mut self, contour: Self::Contour) {
         self.ops.extend_from_slice(&contour.ops);
@@ -61,7 +99,7 @@ impl Outline for Path {
     }
     fn transform(mut self, transform: Transform) -> Self {
         let tr = |p: Point| point(transform * Vector::new(p.x, p.y));
-        
+
         for op in &mut self.ops {
             *op = match *op {
                 PathOp::MoveTo(p) => PathOp::MoveTo(tr(p)),
@@ -76,58 +114,216 @@ impl Outline for Path {
     fn clear(&mut self) {
         self.ops.clear();
     }
+    fn events<'a>(&'a self) -> Box<dyn Iterator<Item=PathEvent> + 'a> {
+        Box::new(self.ops.iter().map(|op| match *op {
+            PathOp::MoveTo(p) => PathEvent::MoveTo(vector(p)),
+            PathOp::LineTo(p) => PathEvent::LineTo(vector(p)),
+            PathOp::QuadTo(c, p) => PathEvent::QuadTo(vector(c), vector(p)),
+            PathOp::CubicTo(c1, c2, p) => PathEvent::CubicTo(vector(c1), vector(c2), vector(p)),
+            PathOp::Close => PathEvent::Close
+        }))
+    }
+}
+
+/// premultiplied ARGB pixels, as raqote expects them
+#[derive(Clone)]
+pub struct Image {
+    width: i32,
+    height: i32,
+    data: Arc<Vec<u32>>,
+}
+
+#[derive(Clone)]
+enum Pattern {
+    Source(Source<'static>),
+    // the transform maps from device space into image space
+    Image(Image, RaTransform),
+}
+impl Pattern {
+    fn with_source<R>(&self, f: impl FnOnce(&Source) -> R) -> R {
+        match *self {
+            Pattern::Source(ref source) => f(source),
+            Pattern::Image(ref image, tr) => {
+                let image = RaImage {
+                    width: image.width,
+                    height: image.height,
+                    data: &image.data
+                };
+                f(&Source::Image(image, ExtendMode::Repeat, FilterMode::Bilinear, tr))
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Style {
-    fill: Option<Source<'static>>,
-    stroke: Option<(Source<'static>, StrokeStyle)>
+    fill: Option<Pattern>,
+    stroke: Option<(Pattern, StrokeStyle)>,
+    winding: Winding,
 }
 
 fn solid((r, g, b, a): Rgba8) -> Source<'static> {
     Source::Solid(SolidSource { r, g, b, a })
 }
+fn gradient(stops: &[GradientStop]) -> Gradient {
+    Gradient {
+        stops: stops.iter().map(|&GradientStop { offset, color: (r, g, b, a) }| RaGradientStop {
+            position: offset,
+            color: Color::new(a, r, g, b)
+        }).collect()
+    }
+}
+fn spread(spread: Spread) -> RaSpread {
+    match spread {
+        Spread::Pad => RaSpread::Pad,
+        Spread::Repeat => RaSpread::Repeat,
+        Spread::Reflect => RaSpread::Reflect,
+    }
+}
+fn pattern(paint: Paint<DrawTarget>) -> Pattern {
+    match paint {
+        Paint::Solid(color) => Pattern::Source(solid(color)),
+        Paint::LinearGradient(g) => Pattern::Source(Source::new_linear_gradient(
+            gradient(&g.stops), point(g.start), point(g.end), spread(g.spread)
+        )),
+        Paint::RadialGradient(g) => Pattern::Source(Source::new_two_circle_radial_gradient(
+            gradient(&g.stops), point(g.focus), 0.0, point(g.center), g.radius, spread(g.spread)
+        )),
+        Paint::Image(image, tr) => Pattern::Image(image, transform(tr.inverse()))
+    }
+}
+fn winding(fill_rule: FillRule) -> Winding {
+    match fill_rule {
+        FillRule::EvenOdd => Winding::EvenOdd,
+        FillRule::NonZero => Winding::NonZero,
+    }
+}
+fn stroke_style(line: LineStyle) -> StrokeStyle {
+    let (join, miter_limit) = match line.join {
+        LineJoin::Miter(limit) => (RaLineJoin::Miter, limit),
+        LineJoin::Bevel => (RaLineJoin::Bevel, StrokeStyle::default().miter_limit),
+        LineJoin::Round => (RaLineJoin::Round, StrokeStyle::default().miter_limit),
+    };
+    StrokeStyle {
+        width: line.width,
+        cap: match line.cap {
+            LineCap::Butt => RaLineCap::Butt,
+            LineCap::Square => RaLineCap::Square,
+            LineCap::Round => RaLineCap::Round,
+        },
+        join,
+        miter_limit,
+        dash_array: if line.is_dashed() { line.dash_array.to_vec() } else { vec![] },
+        dash_offset: line.dash_offset,
+    }
+}
+
+fn blend_mode(mode: BlendMode) -> RaBlendMode {
+    match mode {
+        BlendMode::Normal => RaBlendMode::SrcOver,
+        BlendMode::Multiply => RaBlendMode::Multiply,
+        BlendMode::Screen => RaBlendMode::Screen,
+        BlendMode::Overlay => RaBlendMode::Overlay,
+        BlendMode::Darken => RaBlendMode::Darken,
+        BlendMode::Lighten => RaBlendMode::Lighten,
+        BlendMode::ColorDodge => RaBlendMode::ColorDodge,
+        BlendMode::ColorBurn => RaBlendMode::ColorBurn,
+        BlendMode::HardLight => RaBlendMode::HardLight,
+        BlendMode::SoftLight => RaBlendMode::SoftLight,
+        BlendMode::Difference => RaBlendMode::Difference,
+        BlendMode::Exclusion => RaBlendMode::Exclusion,
+        BlendMode::Hue => RaBlendMode::Hue,
+        BlendMode::Saturation => RaBlendMode::Saturation,
+        BlendMode::Color => RaBlendMode::Color,
+        BlendMode::Luminosity => RaBlendMode::Luminosity,
+    }
+}
+
+#[inline]
+fn premultiply(c: u8, a: u8) -> u32 {
+    (c as u32 * a as u32 + 127) / 255
+}
+#[inline]
+fn argb(r: u8, g: u8, b: u8, a: u8) -> u32 {
+    (a as u32) << 24 | premultiply(r, a) << 16 | premultiply(g, a) << 8 | premultiply(b, a)
+}
+
 impl Surface for DrawTarget {
     type Outline = Path;
     type Style = Style;
     type ClipPath = Path;
-    
+    type Image = Image;
+
     fn new(size: Vector) -> Self {
         DrawTarget::new(size.x().ceil() as i32, size.y().ceil() as i32)
     }
-    fn build_style(&mut self, style: PathStyle) -> Self::Style {
+    fn build_style(&mut self, style: PathStyle<Self>) -> Self::Style {
         Style {
-            fill: style.fill.map(|color| solid(color)),
-            stroke: style.stroke.map(|(color, width)| (
-                solid(color),
-                StrokeStyle {
-                    width: width,
-                    .. StrokeStyle::default()
-                }
-            ))
-        }
-    }
-    
-    fn draw_path(&mut self, path: Path, style: &Style, clip: Option<&Self::ClipPath>) {
+            fill: style.fill.map(pattern),
+            stroke: style.stroke.map(|(paint, line)| (pattern(paint), stroke_style(line))),
+            winding: winding(style.fill_rule)
+        }
+    }
+
+    fn draw_path(&mut self, mut path: Path, style: &Style, clip: Option<&Self::ClipPath>) {
         if let Some(path) = clip {
             self.push_clip(path);
         }
         if let Some(ref fill) = style.fill {
-            self.fill(&path, fill, &DrawOptions::new());
+            path.winding = style.winding;
+            fill.with_source(|source| self.fill(&path, source, &DrawOptions::new()));
         }
-        if let Some((ref stroke, ref style)) = style.stroke {
-            self.stroke(&path, stroke, style, &DrawOptions::new());
+        if let Some((ref stroke, ref stroke_style)) = style.stroke {
+            stroke.with_source(|source| self.stroke(&path, source, stroke_style, &DrawOptions::new()));
         }
         if clip.is_some() {
             self.pop_clip();
         }
     }
 
-    fn clip_path(&mut self, path: Self::Outline, fill_rule: FillRule) -> Self::ClipPath {
+    fn clip_path(&mut self, mut path: Self::Outline, fill_rule: FillRule) -> Self::ClipPath {
+        path.winding = winding(fill_rule);
         path
     }
-    fn texture(&mut self, width: u32, height: u32, data: &[u8], format: PixelFormat) -> Self::Style {
-        unimplemented!
+    fn texture_with_stride(&mut self, width: u32, height: u32, data: &[u8], stride: usize, format: PixelFormat)
+        -> Result<Self::Image, TextureError>
+    {
+        // validate before allocating. a valid buffer holds at least one byte per pixel.
+        let rows = pixels::rows(width, height, data, stride, format)?;
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for row in rows {
+            pixels.extend(pixels::rgba8(row, format).map(|(r, g, b, a)| argb(r, g, b, a)));
+        }
+        Ok(Image {
+            width: width as i32,
+            height: height as i32,
+            data: Arc::new(pixels)
+        })
+    }
+    fn push_layer(&mut self, opacity: f32, mode: BlendMode, clip: Option<&Self::ClipPath>) -> Layer {
+        if let Some(path) = clip {
+            self.push_clip(path);
+        }
+        self.push_layer_with_blend(opacity, blend_mode(mode));
+        // remember whether a clip has been pushed with the layer
+        Layer::new(clip.is_some())
+    }
+    fn pop_layer(&mut self, layer: Layer) {
+        DrawTarget::pop_layer(self);
+        if layer.into_state() == Some(true) {
+            self.pop_clip();
+        }
     }
 }
 
+impl RasterOutput for DrawTarget {
+    fn to_rgba8(&self) -> (u32, u32, Vec<u8>) {
+        let mut data = Vec::with_capacity(self.get_data().len() * 4);
+        for &px in self.get_data() {
+            // premultiplied argb
+            let (r, g, b, a) = pixels::unpremultiply((px >> 16) as u8, (px >> 8) as u8, px as u8, (px >> 24) as u8);
+            data.extend_from_slice(&[r, g, b, a]);
+        }
+        (self.width() as u32, self.height() as u32, data)
+    }
+}
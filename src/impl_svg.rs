@@ -1,5 +1,6 @@
 use std::fmt::{Write};
-use crate::{Surface, Vector, Rgba8, PathStyle, FillRule, PixelFormat, Paint};
+use crate::{Surface, Vector, Rgba8, PathStyle, FillRule, PixelFormat, Paint, GradientStop, Spread, BlendMode, Layer, TextureError, pixels, convert_pixels};
+use crate::png::{self, ColorType};
 use itertools::Itertools;
 use pathfinder_content::outline::{Outline as PaOutline};
 
@@ -9,6 +10,100 @@ impl Svg {
         writeln!(self.0, "</svg>").unwrap();
         self.0
     }
+    fn next_id(&mut self) -> usize {
+        let id = self.1;
+        self.1 += 1;
+        id
+    }
+
+    // writes the definitions needed by `paint` and returns the css for the `fill` or `stroke` property
+    fn paint(&mut self, property: &str, paint: Paint<Self>) -> String {
+        match paint {
+            Paint::Solid((_, _, _, 0)) => format!("{}: none; ", property),
+            Paint::Solid(color) => format!("{}: {}; {}", property, color_str(color), opacity_str(property, color.3)),
+            Paint::LinearGradient(g) => {
+                let id = self.next_id();
+                writeln!(self.0,
+                    "<linearGradient id=\"grad_{}\" gradientUnits=\"userSpaceOnUse\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" spreadMethod=\"{}\">",
+                    id, g.start.x(), g.start.y(), g.end.x(), g.end.y(), spread_str(g.spread)
+                ).unwrap();
+                self.write_stops(&g.stops);
+                writeln!(self.0, "</linearGradient>").unwrap();
+                format!("{}: url(#grad_{}); ", property, id)
+            }
+            Paint::RadialGradient(g) => {
+                let id = self.next_id();
+                writeln!(self.0,
+                    "<radialGradient id=\"grad_{}\" gradientUnits=\"userSpaceOnUse\" cx=\"{}\" cy=\"{}\" r=\"{}\" fx=\"{}\" fy=\"{}\" spreadMethod=\"{}\">",
+                    id, g.center.x(), g.center.y(), g.radius, g.focus.x(), g.focus.y(), spread_str(g.spread)
+                ).unwrap();
+                self.write_stops(&g.stops);
+                writeln!(self.0, "</radialGradient>").unwrap();
+                format!("{}: url(#grad_{}); ", property, id)
+            }
+            Paint::Image(image, tr) => {
+                let id = self.next_id();
+                writeln!(self.0,
+                    "<pattern id=\"pat_{}\" patternUnits=\"userSpaceOnUse\" width=\"{}\" height=\"{}\" patternTransform=\"matrix({} {} {} {} {} {})\"><use href=\"#img_{}\" /></pattern>",
+                    id, image.width, image.height,
+                    tr.m11(), tr.m21(), tr.m12(), tr.m22(), tr.m31(), tr.m32(),
+                    image.id
+                ).unwrap();
+                format!("{}: url(#pat_{}); ", property, id)
+            }
+        }
+    }
+    fn write_stops(&mut self, stops: &[GradientStop]) {
+        for stop in stops {
+            write!(self.0, "<stop offset=\"{}\" stop-color=\"{}\"", stop.offset, color_str(stop.color)).unwrap();
+            if stop.color.3 != 255 {
+                write!(self.0, " stop-opacity=\"{}\"", f(stop.color.3)).unwrap();
+            }
+            writeln!(self.0, " />").unwrap();
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Image {
+    id: usize,
+    width: u32,
+    height: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Style {
+    // content of the style attribute
+    css: String,
+    fill_rule: FillRule,
+}
+
+fn f(u: u8) -> f32 { u as f32 / 255. }
+
+fn color_str((r, g, b, _): Rgba8) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+fn opacity_str(property: &str, a: u8) -> String {
+    match a {
+        255 => String::new(),
+        a => format!("{}-opacity: {}; ", property, f(a))
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity((data.len() + 2) / 3 * 4);
+    for c in data.chunks(3) {
+        let n = (c[0] as u32) << 16 | (*c.get(1).unwrap_or(&0) as u32) << 8 | *c.get(2).unwrap_or(&0) as u32;
+        for i in 0 .. 4 {
+            if i <= c.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
 }
 
 fn fill_rule_str(r: FillRule) -> &'static str {
@@ -18,11 +113,40 @@ fn fill_rule_str(r: FillRule) -> &'static str {
     }
 }
 
+fn blend_mode_str(m: BlendMode) -> &'static str {
+    match m {
+        BlendMode::Normal => "normal",
+        BlendMode::Multiply => "multiply",
+        BlendMode::Screen => "screen",
+        BlendMode::Overlay => "overlay",
+        BlendMode::Darken => "darken",
+        BlendMode::Lighten => "lighten",
+        BlendMode::ColorDodge => "color-dodge",
+        BlendMode::ColorBurn => "color-burn",
+        BlendMode::HardLight => "hard-light",
+        BlendMode::SoftLight => "soft-light",
+        BlendMode::Difference => "difference",
+        BlendMode::Exclusion => "exclusion",
+        BlendMode::Hue => "hue",
+        BlendMode::Saturation => "saturation",
+        BlendMode::Color => "color",
+        BlendMode::Luminosity => "luminosity",
+    }
+}
+
+fn spread_str(s: Spread) -> &'static str {
+    match s {
+        Spread::Pad => "pad",
+        Spread::Repeat => "repeat",
+        Spread::Reflect => "reflect"
+    }
+}
+
 impl Surface for Svg {
     type Outline = PaOutline;
-    type Style = PathStyle<Self>;
+    type Style = Style;
     type ClipPath = usize;
-    type Image = ();
+    type Image = Image;
     
     fn new(size: Vector) -> Self {
         let mut w = String::with_capacity(1024);
@@ -30,45 +154,36 @@ impl Surface for Svg {
         Svg(w, 0)
     }
     fn build_style(&mut self, style: PathStyle<Self>) -> Self::Style {
-        style
-    }
-    fn draw_path(&mut self, path: Self::Outline, style: &Self::Style, clip: Option<&Self::ClipPath>) {
-        write!(self.0, "<path style=\"").unwrap();
-        
-        fn f(u: u8) -> f32 { u as f32 / 255. }
-        match style.fill {
-            None | Some(Paint::Solid((_, _, _, 0))) => {
-                write!(self.0, "fill: none; ").unwrap();
-            }
-            Some(Paint::Solid((r, g, b, a))) => {
-                write!(self.0, "fill: #{:02x}{:02x}{:02x}; ", r, g, b).unwrap();
-                if a != 255 {
-                    write!(self.0, "fill-opacity: {}", f(a)).unwrap();
-                }
-            },
-            _ => unimplemented!()
-        }
-        match style.stroke {
-            Some((Paint::Solid((r, g, b, a)), line_style)) => {
-                write!(self.0, "stroke: #{:02x}{:02x}{:02x}; stroke-width: {}; ", r, g, b, line_style.width).unwrap();
-                if a != 255 {
-                    write!(self.0, "stroke-opacity: {}", a as f32 / 255.).unwrap();
+        let mut css = match style.fill {
+            Some(paint) => self.paint("fill", paint),
+            None => "fill: none; ".into()
+        };
+        if let Some((paint, line_style)) = style.stroke {
+            css.push_str(&self.paint("stroke", paint));
+            write!(css, "stroke-width: {}; ", line_style.width).unwrap();
+            if line_style.is_dashed() {
+                write!(css, "stroke-dasharray: {}; ", line_style.dash_array.iter().format(" ")).unwrap();
+                if line_style.dash_offset != 0.0 {
+                    write!(css, "stroke-dashoffset: {}; ", line_style.dash_offset).unwrap();
                 }
             }
-            None => {}
-            _ => unimplemented!()
         }
-        write!(self.0, "\" fill-rule=\"{}\"", fill_rule_str(style.fill_rule)).unwrap();
+        Style {
+            css,
+            fill_rule: style.fill_rule
+        }
+    }
+    fn draw_path(&mut self, path: Self::Outline, style: &Self::Style, clip: Option<&Self::ClipPath>) {
+        write!(self.0, "<path style=\"{}\" fill-rule=\"{}\"", style.css, fill_rule_str(style.fill_rule)).unwrap();
         
         if let Some(&id) = clip {
-            write!(self.0, " clip-path=\"clip_{}\"", id).unwrap();
+            write!(self.0, " clip-path=\"url(#clip_{})\"", id).unwrap();
         }
 
         writeln!(self.0, " d=\"{:?}\" />", path.contours().iter().format(" ")).unwrap()
     }
     fn clip_path(&mut self, path: Self::Outline, fill_rule: FillRule) -> Self::ClipPath {
-        let id = self.1;
-        self.1 += 1;
+        let id = self.next_id();
 
         writeln!(self.0,
             "<clipPath id=\"clip_{}\"><path clip-rule=\"{}\" d=\"{:?}\" /></clipPath>",
@@ -77,7 +192,45 @@ impl Surface for Svg {
         ).unwrap();
         id
     }
-    fn texture(&mut self, width: u32, height: u32, data: &[u8], format: PixelFormat) -> Self::Image {
-        unimplemented!()
+    fn texture_with_stride(&mut self, width: u32, height: u32, data: &[u8], stride: usize, format: PixelFormat)
+        -> Result<Self::Image, TextureError>
+    {
+        // formats png supports are embedded as they are, everything else as rgba
+        let direct = match format {
+            PixelFormat::L8 => Some(ColorType::Gray),
+            PixelFormat::La16 => Some(ColorType::GrayAlpha),
+            PixelFormat::Rgb24 => Some(ColorType::Rgb),
+            PixelFormat::Rgba32 => Some(ColorType::Rgba),
+            _ => None
+        };
+        let (color_type, packed) = match direct {
+            Some(color_type) => (color_type, pixels::rows(width, height, data, stride, format)?.flatten().cloned().collect()),
+            None => (ColorType::Rgba, convert_pixels(width, height, data, stride, format)?)
+        };
+        let png = png::encode(width, height, color_type, &packed);
+
+        let id = self.next_id();
+        writeln!(self.0,
+            "<defs><image id=\"img_{}\" width=\"{}\" height=\"{}\" href=\"data:image/png;base64,{}\" /></defs>",
+            id, width, height, base64(&png)
+        ).unwrap();
+        Ok(Image { id, width, height })
+    }
+    fn push_layer(&mut self, opacity: f32, blend_mode: BlendMode, clip: Option<&Self::ClipPath>) -> Layer {
+        write!(self.0, "<g").unwrap();
+        if opacity != 1.0 {
+            write!(self.0, " opacity=\"{}\"", opacity).unwrap();
+        }
+        if blend_mode != BlendMode::Normal {
+            write!(self.0, " style=\"mix-blend-mode: {}\"", blend_mode_str(blend_mode)).unwrap();
+        }
+        if let Some(&id) = clip {
+            write!(self.0, " clip-path=\"url(#clip_{})\"", id).unwrap();
+        }
+        writeln!(self.0, ">").unwrap();
+        Layer::empty()
+    }
+    fn pop_layer(&mut self, _layer: Layer) {
+        writeln!(self.0, "</g>").unwrap();
     }
 }
@@ -24,6 +24,128 @@ pub trait Contour: Clone + Sized {
     fn clear(&mut self);
 }
 
+/// Approximate the arc described by `Contour::arc` with cubic bezier curves.
+///
+/// Returns the start point of the arc and the (c1, c2, p) triples of each curve.
+/// `clockwise` means increasing angles (which appear clockwise with y pointing down).
+pub(crate) fn arc_to_cubics(transform: Transform, start_angle: f32, end_angle: f32, clockwise: bool)
+    -> (Vector, impl Iterator<Item=(Vector, Vector, Vector)>)
+{
+    use std::f32::consts::PI;
+
+    let mut sweep = end_angle - start_angle;
+    if sweep.abs() >= 2.0 * PI - 1e-6 {
+        sweep = if clockwise { 2.0 * PI } else { -2.0 * PI };
+    } else if clockwise && sweep < 0.0 {
+        sweep += 2.0 * PI;
+    } else if !clockwise && sweep > 0.0 {
+        sweep -= 2.0 * PI;
+    }
+
+    // at most a quarter circle per curve
+    let n = (sweep.abs() / (0.5 * PI)).ceil().max(1.0) as usize;
+    let step = sweep / n as f32;
+    let k = 4.0 / 3.0 * (0.25 * step).tan();
+
+    let unit = |a: f32| Vector::new(a.cos(), a.sin());
+    let start = transform * unit(start_angle);
+    let curves = (0 .. n).map(move |i| {
+        let a0 = start_angle + step * i as f32;
+        let a1 = a0 + step;
+        let (p0, p1) = (unit(a0), unit(a1));
+        let c1 = p0 + Vector::new(-p0.y(), p0.x()) * k;
+        let c2 = p1 - Vector::new(-p1.y(), p1.x()) * k;
+        (transform * c1, transform * c2, transform * p1)
+    });
+    (start, curves)
+}
+
+/// The curves of an SVG style endpoint arc from `from` to `to`, ending exactly in `to`.
+///
+/// Returns `None` if the arc is a line or nothing, see `endpoint_to_center`.
+fn arc_to_curves(from: Vector, radii: Vector, x_rotation: f32, large_arc: bool, sweep: bool, to: Vector)
+    -> Option<Vec<(Vector, Vector, Vector)>>
+{
+    let (transform, start_angle, end_angle) = endpoint_to_center(from, radii, x_rotation, large_arc, sweep, to)?;
+    let (_, curves) = arc_to_cubics(transform, start_angle, end_angle, sweep);
+    let mut curves: Vec<_> = curves.collect();
+    // hit the endpoint exactly
+    if let Some(last) = curves.last_mut() {
+        last.2 = to;
+    }
+    Some(curves)
+}
+
+/// Convert an SVG style endpoint arc into the center parameterization used by `Contour::arc`.
+///
+/// Returns the transform of the unit circle and the start and end angle,
+/// or `None` if the arc degenerates into a line (zero radius) or nothing (`from == to`).
+/// Radii that are too small to reach `to` are scaled up as described in the SVG spec (F.6.6).
+pub(crate) fn endpoint_to_center(from: Vector, radii: Vector, x_rotation: f32, large_arc: bool, sweep: bool, to: Vector)
+    -> Option<(Transform, f32, f32)>
+{
+    use std::f32::consts::PI;
+
+    let (mut rx, mut ry) = (radii.x().abs(), radii.y().abs());
+    if from == to || rx == 0.0 || ry == 0.0 {
+        return None;
+    }
+
+    // F.6.5.1: move the midpoint into the origin and undo the rotation
+    let p = Transform::from_rotation(-x_rotation) * ((from - to) * 0.5);
+    let (x1, y1) = (p.x(), p.y());
+
+    // F.6.6.2: scale up radii that are too small
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        let s = lambda.sqrt();
+        rx *= s;
+        ry *= s;
+    }
+
+    // F.6.5.2: center in the rotated frame
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coef = (num / den).max(0.0).sqrt();
+    if large_arc == sweep {
+        coef = -coef;
+    }
+    let c = Vector::new(rx * y1 / ry, -ry * x1 / rx) * coef;
+
+    // F.6.5.3: back into user space
+    let center = Transform::from_rotation(x_rotation) * c + (from + to) * 0.5;
+
+    // F.6.5.5/6: angles on the unit circle
+    let angle = |v: Vector| v.y().atan2(v.x());
+    let u = Vector::new((x1 - c.x()) / rx, (y1 - c.y()) / ry);
+    let v = Vector::new((-x1 - c.x()) / rx, (-y1 - c.y()) / ry);
+    let start = angle(u);
+    let mut delta = angle(v) - start;
+    if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    } else if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    }
+
+    let transform = Transform::from_translation(center)
+        * Transform::from_rotation(x_rotation)
+        * Transform::from_scale(Vector::new(rx, ry));
+    Some((transform, start, start + delta))
+}
+
+/// One segment of an outline, as produced by `Outline::events`.
+///
+/// Every contour starts with a `MoveTo` and closed contours end with `Close`.
+/// Arcs are reported as cubic curves.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PathEvent {
+    MoveTo(Vector),
+    LineTo(Vector),
+    QuadTo(Vector, Vector),
+    CubicTo(Vector, Vector, Vector),
+    Close,
+}
+
 pub trait Outline: Clone + Sized {
     type Contour: Contour;
     
@@ -33,6 +155,79 @@ pub trait Outline: Clone + Sized {
     fn bounding_box(&self) -> Option<Rect>;
     fn transform(self, transform: Transform) -> Self;
     fn clear(&mut self);
+
+    /// iterate over the segments of all contours
+    fn events<'a>(&'a self) -> Box<dyn Iterator<Item=PathEvent> + 'a>;
+
+    /// Convert into another outline type by replaying the segments through its `Contour`.
+    ///
+    /// This allows drawing a `PaOutline` onto a raqote `DrawTarget` and vice versa.
+    fn to_outline<O: Outline>(&self) -> O {
+        let mut outline = O::empty();
+        let mut contour = O::Contour::new();
+        for event in self.events() {
+            match event {
+                PathEvent::MoveTo(p) => {
+                    if !contour.is_empty() {
+                        outline.add_contour(contour.clone());
+                        contour.clear();
+                    }
+                    contour.move_to(p);
+                }
+                PathEvent::LineTo(p) => contour.line_to(p),
+                PathEvent::QuadTo(c, p) => contour.quadratic_curve_to(c, p),
+                PathEvent::CubicTo(c1, c2, p) => contour.cubic_curve_to(c1, c2, p),
+                PathEvent::Close => contour.close(),
+            }
+        }
+        if !contour.is_empty() {
+            outline.add_contour(contour);
+        }
+        outline
+    }
+
+    /// Approximate all curves by lines, with an error of at most `tolerance`.
+    ///
+    /// See `flatten::flatten`.
+    fn flatten<O: Outline>(&self, tolerance: f32) -> O {
+        flatten::flatten(self, tolerance)
+    }
+
+    /// The area covered by stroking this outline with `style`, as an outline to be filled with `FillRule::NonZero`.
+    ///
+    /// See `stroke::stroke_to_fill`.
+    fn stroke_to_fill<O: Outline>(&self, style: &LineStyle, tolerance: f32) -> O {
+        stroke::stroke_to_fill(self, style, tolerance)
+    }
+
+    /// Combine this outline with `other`, using the same fill rule for both.
+    ///
+    /// See `boolean::boolean`.
+    fn boolean<B: Outline, O: Outline>(&self, op: BooleanOp, other: &B, fill_rule: FillRule, tolerance: f32) -> O {
+        boolean::boolean(op, self, fill_rule, other, fill_rule, tolerance)
+    }
+
+    /// Whether `p` is inside of the outline when filled with `fill_rule`.
+    ///
+    /// See `hit_test::contains`.
+    fn contains(&self, p: Vector, fill_rule: FillRule, tolerance: f32) -> bool {
+        hit_test::contains(self, p, fill_rule, tolerance)
+    }
+
+    /// Whether `p` is covered by the stroke of the outline.
+    ///
+    /// See `hit_test::stroke_contains`.
+    fn stroke_contains(&self, p: Vector, style: &LineStyle, tolerance: f32) -> bool {
+        hit_test::stroke_contains(self, p, style, tolerance)
+    }
+}
+
+// maps the unit circle onto the ellipse
+#[inline]
+fn ellipse_transform(center: Vector, radius: Vector, phi: f32) -> Transform {
+    Transform::from_translation(center)
+        * Transform::from_rotation(phi)
+        * Transform::from_scale(radius)
 }
 
 #[derive(Copy, Clone)]
@@ -47,26 +242,126 @@ enum PathState {
     End(Vector)
 }
 
+// the control point of the last segment, which the smooth curves reflect
+#[derive(Copy, Clone)]
+enum LastControl {
+    None,
+    Quadratic(Vector),
+    Cubic(Vector),
+}
+
+/// Why a segment could not be added to a `PathBuilder`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PathError {
+    /// drawing without a current point
+    NoStartPoint,
+    /// a coordinate is NaN or infinite
+    NonFinite(Vector),
+}
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PathError::NoStartPoint => write!(f, "no starting point set. call move_to first"),
+            PathError::NonFinite(p) => write!(f, "non-finite coordinates ({}, {})", p.x(), p.y()),
+        }
+    }
+}
+impl std::error::Error for PathError {}
+
+/// What the drawing methods of `PathBuilder` do with invalid input.
+///
+/// The `try_` methods return a `PathError` instead, regardless of the policy.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ErrorPolicy {
+    /// panic (the default)
+    Panic,
+    /// start at the origin if there is no current point, drop segments with non-finite coordinates
+    MoveToOrigin,
+    /// drop the segment
+    Ignore,
+}
+
 #[derive(Copy, Clone)]
 pub struct PathBuilder<O: Outline> {
     outline: O,
     contour: O::Contour,
     state: PathState,
+    // where the current contour started, which is where `close` returns to
+    contour_start: Vector,
+    control: LastControl,
+    policy: ErrorPolicy,
+}
+impl<O: Outline> Default for PathBuilder<O> {
+    #[inline]
+    fn default() -> Self {
+        PathBuilder::new()
+    }
 }
 impl<O: Outline> PathBuilder<O> {
     #[inline]
     pub fn new() -> Self {
+        PathBuilder::with_policy(ErrorPolicy::Panic)
+    }
+    #[inline]
+    pub fn with_policy(policy: ErrorPolicy) -> Self {
         PathBuilder {
             outline: O::empty(),
             contour: O::Contour::new(),
-            state: PathState::Empty
+            state: PathState::Empty,
+            contour_start: Vector::default(),
+            control: LastControl::None,
+            policy,
+        }
+    }
+    #[inline]
+    pub fn set_policy(&mut self, policy: ErrorPolicy) {
+        self.policy = policy;
+    }
+
+    // check every point a command computed from its arguments
+    fn check_all(&self, segments: &[(Vector, Vector, Vector)], needs_start: bool) -> Result<(), PathError> {
+        for &(c1, c2, p) in segments {
+            self.check(&[c1, c2, p], needs_start)?;
+        }
+        Ok(())
+    }
+
+    fn check(&self, points: &[Vector], needs_start: bool) -> Result<(), PathError> {
+        if let Some(&p) = points.iter().find(|p| !(p.x().is_finite() && p.y().is_finite())) {
+            return Err(PathError::NonFinite(p));
+        }
+        match self.state {
+            PathState::Empty if needs_start => Err(PathError::NoStartPoint),
+            _ => Ok(())
+        }
+    }
+
+    // `accept` for all of the (c1, c2, p) segments, stopping at the first invalid one
+    fn accept_all(&mut self, segments: &[(Vector, Vector, Vector)], needs_start: bool) -> bool {
+        segments.iter().all(|&(c1, c2, p)| self.accept(&[c1, c2, p], needs_start))
+    }
+
+    // apply the policy if the segment through `points` is invalid. returns whether to draw it.
+    fn accept(&mut self, points: &[Vector], needs_start: bool) -> bool {
+        let error = match self.check(points, needs_start) {
+            Ok(()) => return true,
+            Err(e) => e
+        };
+        match (self.policy, error) {
+            (ErrorPolicy::Panic, e) => panic!("{}", e),
+            (ErrorPolicy::MoveToOrigin, PathError::NoStartPoint) => {
+                self.state = PathState::Start(Vector::default());
+                true
+            }
+            _ => false
         }
     }
 
     #[inline]
     fn start(&mut self) {
         match self.state {
-            PathState::Empty => panic!("no starting point set. call move_to first"),
+            // excluded by `accept`
+            PathState::Empty => {}
             PathState::Start(p) => {
                 // copy the contour instead of allocating a new buffer with unknown size each time
                 // that way we reuse one buffer for each contour (of unknown length) and only need one allocation per contour
@@ -76,6 +371,7 @@ impl<O: Outline> PathBuilder<O> {
                     self.contour.clear();
                 }
                 self.contour.move_to(p);
+                self.contour_start = p;
             }
             PathState::End(_) => {}
         }
@@ -83,50 +379,451 @@ impl<O: Outline> PathBuilder<O> {
 
     #[inline]
     pub fn move_to(&mut self, p: Vector) {
+        if !self.accept(&[p], false) {
+            return;
+        }
         self.state = PathState::Start(p);
+        self.control = LastControl::None;
     }
     #[inline]
     pub fn line_to(&mut self, p: Vector) {
+        if !self.accept(&[p], true) {
+            return;
+        }
         self.start();
         self.contour.line_to(p);
         self.state = PathState::End(p);
+        self.control = LastControl::None;
     }
     #[inline]
     pub fn quadratic_curve_to(&mut self, c: Vector, p: Vector) {
+        if !self.accept(&[c, p], true) {
+            return;
+        }
         self.start();
         self.contour.quadratic_curve_to(c, p);
         self.state = PathState::End(p);
+        self.control = LastControl::Quadratic(c);
     }
     #[inline]
     pub fn cubic_curve_to(&mut self, c1: Vector, c2: Vector, p: Vector) {
+        if !self.accept(&[c1, c2, p], true) {
+            return;
+        }
         self.start();
         self.contour.cubic_curve_to(c1, c2, p);
         self.state = PathState::End(p);
+        self.control = LastControl::Cubic(c2);
+    }
+
+    /// Like `move_to`, but returns an error instead of applying the policy.
+    pub fn try_move_to(&mut self, p: Vector) -> Result<(), PathError> {
+        self.check(&[p], false)?;
+        self.move_to(p);
+        Ok(())
+    }
+    /// Like `line_to`, but returns an error instead of applying the policy.
+    pub fn try_line_to(&mut self, p: Vector) -> Result<(), PathError> {
+        self.check(&[p], true)?;
+        self.line_to(p);
+        Ok(())
+    }
+    /// Like `quadratic_curve_to`, but returns an error instead of applying the policy.
+    pub fn try_quadratic_curve_to(&mut self, c: Vector, p: Vector) -> Result<(), PathError> {
+        self.check(&[c, p], true)?;
+        self.quadratic_curve_to(c, p);
+        Ok(())
+    }
+    /// Like `cubic_curve_to`, but returns an error instead of applying the policy.
+    pub fn try_cubic_curve_to(&mut self, c1: Vector, c2: Vector, p: Vector) -> Result<(), PathError> {
+        self.check(&[c1, c2, p], true)?;
+        self.cubic_curve_to(c1, c2, p);
+        Ok(())
+    }
+
+    // the current point, or the origin if there is none. relative commands are relative to this.
+    #[inline]
+    fn current(&self) -> Vector {
+        self.pos().unwrap_or_default()
+    }
+    #[inline]
+    pub fn rel_move_to(&mut self, d: Vector) {
+        let p = self.current() + d;
+        self.move_to(p);
+    }
+    #[inline]
+    pub fn rel_line_to(&mut self, d: Vector) {
+        let p = self.current() + d;
+        self.line_to(p);
+    }
+    #[inline]
+    pub fn rel_quadratic_curve_to(&mut self, c: Vector, d: Vector) {
+        let o = self.current();
+        self.quadratic_curve_to(o + c, o + d);
+    }
+    #[inline]
+    pub fn rel_cubic_curve_to(&mut self, c1: Vector, c2: Vector, d: Vector) {
+        let o = self.current();
+        self.cubic_curve_to(o + c1, o + c2, o + d);
+    }
+    #[inline]
+    pub fn horizontal_line_to(&mut self, x: f32) {
+        let p = Vector::new(x, self.current().y());
+        self.line_to(p);
+    }
+    #[inline]
+    pub fn vertical_line_to(&mut self, y: f32) {
+        let p = Vector::new(self.current().x(), y);
+        self.line_to(p);
+    }
+    #[inline]
+    pub fn rel_horizontal_line_to(&mut self, dx: f32) {
+        self.rel_line_to(Vector::new(dx, 0.0));
+    }
+    #[inline]
+    pub fn rel_vertical_line_to(&mut self, dy: f32) {
+        self.rel_line_to(Vector::new(0.0, dy));
+    }
+
+    /// Quadratic curve whose control point is the reflection of the previous one,
+    /// or the current point if the last segment was not a quadratic curve.
+    #[inline]
+    pub fn smooth_quadratic_curve_to(&mut self, p: Vector) {
+        let c = self.smooth_quadratic_control();
+        self.quadratic_curve_to(c, p);
+    }
+    /// Cubic curve whose first control point is the reflection of the previous second one,
+    /// or the current point if the last segment was not a cubic curve.
+    #[inline]
+    pub fn smooth_cubic_curve_to(&mut self, c2: Vector, p: Vector) {
+        let c1 = self.smooth_cubic_control();
+        self.cubic_curve_to(c1, c2, p);
+    }
+    #[inline]
+    pub fn rel_smooth_quadratic_curve_to(&mut self, d: Vector) {
+        let p = self.current() + d;
+        self.smooth_quadratic_curve_to(p);
     }
     #[inline]
+    pub fn rel_smooth_cubic_curve_to(&mut self, c2: Vector, d: Vector) {
+        let o = self.current();
+        self.smooth_cubic_curve_to(o + c2, o + d);
+    }
+    #[inline]
+    pub fn rel_arc_to(&mut self, radius: Vector, x_rotation: f32, large_arc: bool, sweep: bool, d: Vector) {
+        let p = self.current() + d;
+        self.arc_to(radius, x_rotation, large_arc, sweep, p);
+    }
+
+    #[inline]
+    fn smooth_quadratic_control(&self) -> Vector {
+        let o = self.current();
+        match self.control {
+            LastControl::Quadratic(c) => o * 2.0 - c,
+            _ => o
+        }
+    }
+    #[inline]
+    fn smooth_cubic_control(&self) -> Vector {
+        let o = self.current();
+        match self.control {
+            LastControl::Cubic(c) => o * 2.0 - c,
+            _ => o
+        }
+    }
+
+    // The `try_` variants of the commands above. Coordinates are checked after they have been
+    // made absolute, so a relative step that overflows is an error as well.
+
+    pub fn try_rel_move_to(&mut self, d: Vector) -> Result<(), PathError> {
+        let p = self.current() + d;
+        self.try_move_to(p)
+    }
+    pub fn try_rel_line_to(&mut self, d: Vector) -> Result<(), PathError> {
+        let p = self.current() + d;
+        self.try_line_to(p)
+    }
+    pub fn try_rel_quadratic_curve_to(&mut self, c: Vector, d: Vector) -> Result<(), PathError> {
+        let o = self.current();
+        self.try_quadratic_curve_to(o + c, o + d)
+    }
+    pub fn try_rel_cubic_curve_to(&mut self, c1: Vector, c2: Vector, d: Vector) -> Result<(), PathError> {
+        let o = self.current();
+        self.try_cubic_curve_to(o + c1, o + c2, o + d)
+    }
+    pub fn try_horizontal_line_to(&mut self, x: f32) -> Result<(), PathError> {
+        let p = Vector::new(x, self.current().y());
+        self.try_line_to(p)
+    }
+    pub fn try_vertical_line_to(&mut self, y: f32) -> Result<(), PathError> {
+        let p = Vector::new(self.current().x(), y);
+        self.try_line_to(p)
+    }
+    pub fn try_rel_horizontal_line_to(&mut self, dx: f32) -> Result<(), PathError> {
+        self.try_rel_line_to(Vector::new(dx, 0.0))
+    }
+    pub fn try_rel_vertical_line_to(&mut self, dy: f32) -> Result<(), PathError> {
+        self.try_rel_line_to(Vector::new(0.0, dy))
+    }
+    pub fn try_smooth_quadratic_curve_to(&mut self, p: Vector) -> Result<(), PathError> {
+        let c = self.smooth_quadratic_control();
+        self.try_quadratic_curve_to(c, p)
+    }
+    pub fn try_smooth_cubic_curve_to(&mut self, c2: Vector, p: Vector) -> Result<(), PathError> {
+        let c1 = self.smooth_cubic_control();
+        self.try_cubic_curve_to(c1, c2, p)
+    }
+    pub fn try_rel_smooth_quadratic_curve_to(&mut self, d: Vector) -> Result<(), PathError> {
+        let p = self.current() + d;
+        self.try_smooth_quadratic_curve_to(p)
+    }
+    pub fn try_rel_smooth_cubic_curve_to(&mut self, c2: Vector, d: Vector) -> Result<(), PathError> {
+        let o = self.current();
+        self.try_smooth_cubic_curve_to(o + c2, o + d)
+    }
+    pub fn try_rel_arc_to(&mut self, radius: Vector, x_rotation: f32, large_arc: bool, sweep: bool, d: Vector) -> Result<(), PathError> {
+        let p = self.current() + d;
+        self.try_arc_to(radius, x_rotation, large_arc, sweep, p)
+    }
+    // The closed shapes below each add a separate contour. Afterwards, the current point is where the contour started.
+    // Rectangles run in the direction of increasing angles (clockwise with y pointing down), ellipses the other way.
+
+    /// Closed contour starting at the origin of `rect`.
+    #[inline]
     pub fn rect(&mut self, rect: Rect) {
         self.move_to(rect.origin());
         self.line_to(rect.upper_right());
         self.line_to(rect.lower_right());
         self.line_to(rect.lower_left());
         self.close();
-        self.state = PathState::End(rect.lower_left());
     }
+    /// Closed contour starting at the rightmost point of the circle.
     #[inline]
     pub fn circle(&mut self, center: Vector, radius: f32) {
         self.ellipse(center, Vector::splat(radius), 0.0);
     }
+    /// Closed contour starting at the end of the first axis (`radius.x()`, rotated by `phi`).
+    ///
+    /// Runs in the direction of decreasing angles.
     #[inline]
     pub fn ellipse(&mut self, center: Vector, radius: Vector, phi: f32) {
-        let transform = Transform::from_translation(center)
-            * Transform::from_rotation(phi)
-            * Transform::from_scale(radius);
-        self.contour.arc(transform, 0.0, 2.0 * core::f32::consts::PI, false);
-        self.contour.close();
+        // the same point `arc` starts at, so they are not connected by a line
+        self.move_to(ellipse_transform(center, radius, phi) * Vector::new(1.0, 0.0));
+        self.arc(center, radius, phi, 0.0, 2.0 * core::f32::consts::PI, false);
+        self.close();
+    }
+    /// Rectangle with elliptical corners.
+    ///
+    /// `radii` are given clockwise from the top left corner (with y pointing down).
+    /// A corner with a zero radius is sharp. Radii that do not fit are scaled down like in CSS.
+    pub fn rounded_rect(&mut self, rect: Rect, radii: [Vector; 4]) {
+        use std::f32::consts::PI;
+
+        let mut radii = radii;
+        for r in radii.iter_mut() {
+            *r = r.max(Vector::default());
+        }
+        let (w, h) = (rect.width(), rect.height());
+        let sums = [
+            (radii[0].x() + radii[1].x(), w),
+            (radii[3].x() + radii[2].x(), w),
+            (radii[0].y() + radii[3].y(), h),
+            (radii[1].y() + radii[2].y(), h),
+        ];
+        let scale = sums.iter().fold(1.0f32, |f, &(sum, len)| if sum > len { f.min(len / sum) } else { f });
+
+        // the corners and the direction of the edge that starts there
+        let corners = [
+            (rect.origin(), Vector::new(1.0, 0.0)),
+            (rect.upper_right(), Vector::new(0.0, 1.0)),
+            (rect.lower_right(), Vector::new(-1.0, 0.0)),
+            (rect.lower_left(), Vector::new(0.0, -1.0)),
+        ];
+        // the radius in the direction of `d`
+        let along = |r: Vector, d: Vector| r.x() * d.x().abs() + r.y() * d.y().abs();
+        let k = 4.0 / 3.0 * (PI / 8.0).tan();
+
+        self.move_to(rect.origin() + Vector::new(1.0, 0.0) * along(radii[0] * scale, Vector::new(1.0, 0.0)));
+        for i in 1 ..= 4 {
+            let (corner, d) = corners[i % 4];
+            let (_, d_prev) = corners[i - 1];
+            let r = radii[i % 4] * scale;
+            let a = corner - d_prev * along(r, d_prev);
+            self.line_to(a);
+            if r.x() > 0.0 && r.y() > 0.0 {
+                let b = corner + d * along(r, d);
+                self.cubic_curve_to(a + d_prev * (k * along(r, d_prev)), b - d * (k * along(r, d)), b);
+            }
+        }
+        self.close();
+    }
+
+    /// Open contour through `points`. Does nothing if `points` is empty.
+    pub fn polyline(&mut self, points: &[Vector]) {
+        if let Some((&first, rest)) = points.split_first() {
+            self.move_to(first);
+            for &p in rest {
+                self.line_to(p);
+            }
+        }
+    }
+
+    /// Closed contour through `points`. Does nothing if `points` is empty.
+    pub fn polygon(&mut self, points: &[Vector]) {
+        if !points.is_empty() {
+            self.polyline(points);
+            self.close();
+        }
+    }
+
+    /// Polygon with `sides` corners on the circle around `center`.
+    ///
+    /// The first corner is at the angle `rotation`, measured from the x axis towards the y axis.
+    pub fn regular_polygon(&mut self, center: Vector, radius: f32, sides: usize, rotation: f32) {
+        use std::f32::consts::PI;
+
+        let points: Vec<Vector> = (0 .. sides)
+            .map(|i| {
+                let angle = rotation + 2.0 * PI * i as f32 / sides as f32;
+                center + Vector::new(angle.cos(), angle.sin()) * radius
+            })
+            .collect();
+        self.polygon(&points);
+    }
+
+    /// Star with `points` tips at `outer_radius` and the inner corners at `inner_radius`.
+    ///
+    /// The first tip is at the angle `rotation`.
+    pub fn star(&mut self, center: Vector, outer_radius: f32, inner_radius: f32, points: usize, rotation: f32) {
+        use std::f32::consts::PI;
+
+        let corners: Vec<Vector> = (0 .. 2 * points)
+            .map(|i| {
+                let angle = rotation + PI * i as f32 / points as f32;
+                let radius = if i % 2 == 0 { outer_radius } else { inner_radius };
+                center + Vector::new(angle.cos(), angle.sin()) * radius
+            })
+            .collect();
+        self.polygon(&corners);
+    }
+
+    /// Superellipse `|x / rx|ⁿ + |y / ry|ⁿ = 1` around `center`.
+    ///
+    /// `exponent` 2 gives an ellipse, larger values approach a rectangle ("squircle")
+    /// and values between 0 and 1 give a star like shape. The curve is interpolated through 32 points.
+    /// Does nothing if `exponent` is not positive.
+    pub fn superellipse(&mut self, center: Vector, radius: Vector, exponent: f32) {
+        use std::f32::consts::PI;
+        const SAMPLES: usize = 32;
+
+        // the powers below are infinite for exponents up to 0
+        if exponent.is_nan() || exponent <= 0.0 {
+            return;
+        }
+        let e = 2.0 / exponent;
+        let points: Vec<Vector> = (0 .. SAMPLES)
+            .map(|i| {
+                let t = 2.0 * PI * i as f32 / SAMPLES as f32;
+                let (sin, cos) = t.sin_cos();
+                let x = cos.signum() * cos.abs().powf(e);
+                let y = sin.signum() * sin.abs().powf(e);
+                center + Vector::new(x * radius.x(), y * radius.y())
+            })
+            .collect();
+
+        // catmull-rom spline through the points
+        let p = |i: usize| points[i % SAMPLES];
+        self.move_to(p(0));
+        for i in SAMPLES .. 2 * SAMPLES {
+            let c1 = p(i) + (p(i + 1) - p(i - 1)) * (1.0 / 6.0);
+            let c2 = p(i + 1) - (p(i + 2) - p(i)) * (1.0 / 6.0);
+            self.cubic_curve_to(c1, c2, p(i + 1));
+        }
+        self.close();
+    }
+
+    /// Elliptical arc around `center`, in the parameterization of `Contour::arc`.
+    ///
+    /// The ellipse has the radii `radius` and is rotated by `phi`. `clockwise` means increasing angles.
+    /// Like in canvas, the arc is connected to the current point with a line.
+    /// If any of its curves would have non-finite coordinates, the error policy applies to the whole arc.
+    pub fn arc(&mut self, center: Vector, radius: Vector, phi: f32, start_angle: f32, end_angle: f32, clockwise: bool) {
+        if !self.accept(&[center, radius, Vector::new(start_angle, end_angle), Vector::new(phi, 0.0)], false) {
+            return;
+        }
+        let (start, curves) = arc_to_cubics(ellipse_transform(center, radius, phi), start_angle, end_angle, clockwise);
+        // all or nothing, so a rejected curve does not leave a partial arc behind
+        let curves: Vec<_> = curves.collect();
+        if !self.accept(&[start], false) || !self.accept_all(&curves, false) {
+            return;
+        }
+        match self.pos() {
+            None => self.move_to(start),
+            Some(p) if p != start => self.line_to(start),
+            Some(_) => {}
+        }
+        for (c1, c2, p) in curves {
+            self.cubic_curve_to(c1, c2, p);
+        }
+        self.control = LastControl::None;
+    }
+
+    /// Elliptical arc from the current point to `p`, like the `A` command of SVG path data.
+    ///
+    /// `x_rotation` is in radians. As in the SVG spec, the arc is omitted if it ends in the current point,
+    /// becomes a line if one of the radii is zero, and radii that are too small to reach `p` are scaled up.
+    /// If any of its curves would have non-finite coordinates, the error policy applies to the whole arc.
+    pub fn arc_to(&mut self, radius: Vector, x_rotation: f32, large_arc: bool, sweep: bool, p: Vector) {
+        if !self.accept(&[radius, Vector::new(x_rotation, 0.0), p], true) {
+            return;
+        }
+        let from = self.current();
+        match arc_to_curves(from, radius, x_rotation, large_arc, sweep, p) {
+            Some(curves) => {
+                if !self.accept_all(&curves, true) {
+                    return;
+                }
+                for (c1, c2, q) in curves {
+                    self.cubic_curve_to(c1, c2, q);
+                }
+            }
+            None if from != p => self.line_to(p),
+            None => {}
+        }
+        self.control = LastControl::None;
+    }
+
+    /// Like `arc`, but returns an error instead of applying the policy.
+    ///
+    /// Nothing is added if any of the curves would have non-finite coordinates.
+    pub fn try_arc(&mut self, center: Vector, radius: Vector, phi: f32, start_angle: f32, end_angle: f32, clockwise: bool) -> Result<(), PathError> {
+        self.check(&[center, radius, Vector::new(start_angle, end_angle), Vector::new(phi, 0.0)], false)?;
+        let (start, curves) = arc_to_cubics(ellipse_transform(center, radius, phi), start_angle, end_angle, clockwise);
+        self.check(&[start], false)?;
+        self.check_all(&curves.collect::<Vec<_>>(), false)?;
+        self.arc(center, radius, phi, start_angle, end_angle, clockwise);
+        Ok(())
+    }
+    /// Like `arc_to`, but returns an error instead of applying the policy.
+    ///
+    /// Nothing is added if any of the curves would have non-finite coordinates.
+    pub fn try_arc_to(&mut self, radius: Vector, x_rotation: f32, large_arc: bool, sweep: bool, p: Vector) -> Result<(), PathError> {
+        self.check(&[radius, Vector::new(x_rotation, 0.0), p], true)?;
+        if let Some(curves) = arc_to_curves(self.current(), radius, x_rotation, large_arc, sweep, p) {
+            self.check_all(&curves, true)?;
+        }
+        self.arc_to(radius, x_rotation, large_arc, sweep, p);
+        Ok(())
     }
+
+    /// Close the current contour. Drawing continues with a new contour at its start point.
     #[inline]
     pub fn close(&mut self) {
-        self.contour.close();
+        if let PathState::End(_) = self.state {
+            self.contour.close();
+            self.state = PathState::Start(self.contour_start);
+        }
+        self.control = LastControl::None;
     }
     #[inline]
     pub fn into_outline(mut self) -> O {
@@ -157,6 +854,7 @@ impl<O: Outline> PathBuilder<O> {
         self.contour.clear();
         self.outline.clear();
         self.state = PathState::Empty;
+        self.control = LastControl::None;
     }
 
     #[inline]
@@ -175,16 +873,114 @@ pub enum FillRule {
     NonZero
 }
 
+/// what happens outside of the range covered by the gradient stops
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Spread {
+    /// extend the first and last color
+    Pad,
+    /// start over with the first stop
+    Repeat,
+    /// alternate between forward and backward
+    Reflect,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GradientStop {
+    /// position of the stop in the range 0 to 1
+    pub offset: f32,
+    pub color: Rgba8,
+}
+impl GradientStop {
+    #[inline]
+    pub fn new(offset: f32, color: Rgba8) -> Self {
+        GradientStop { offset, color }
+    }
+}
+
+// the stops forward in the first half and backward in the second half
+fn mirrored_stops(stops: &[GradientStop]) -> Vec<GradientStop> {
+    let forward = stops.iter().map(|s| GradientStop::new(0.5 * s.offset, s.color));
+    let backward = stops.iter().rev().map(|s| GradientStop::new(1.0 - 0.5 * s.offset, s.color));
+    forward.chain(backward).collect()
+}
+
+/// a gradient along the line from `start` (offset 0) to `end` (offset 1)
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearGradient {
+    pub start: Vector,
+    pub end: Vector,
+    pub stops: Vec<GradientStop>,
+    pub spread: Spread,
+}
+impl LinearGradient {
+    #[inline]
+    pub fn new(start: Vector, end: Vector, stops: Vec<GradientStop>) -> Self {
+        LinearGradient { start, end, stops, spread: Spread::Pad }
+    }
+    /// The same gradient with `Spread::Repeat` instead of `Spread::Reflect`, for backends that can not reflect.
+    ///
+    /// The line is twice as long and runs through the stops forward and then backward.
+    /// Other spreads are returned unchanged.
+    pub fn reflect_as_repeat(&self) -> LinearGradient {
+        if self.spread != Spread::Reflect {
+            return self.clone();
+        }
+        LinearGradient {
+            start: self.start,
+            end: self.start + (self.end - self.start) * 2.0,
+            stops: mirrored_stops(&self.stops),
+            spread: Spread::Repeat,
+        }
+    }
+}
+
+/// a gradient from the `focus` (offset 0) to the circle around `center` with `radius` (offset 1)
+#[derive(Clone, Debug, PartialEq)]
+pub struct RadialGradient {
+    pub center: Vector,
+    pub radius: f32,
+    pub focus: Vector,
+    pub stops: Vec<GradientStop>,
+    pub spread: Spread,
+}
+impl RadialGradient {
+    /// a radial gradient with the focus in the center
+    #[inline]
+    pub fn new(center: Vector, radius: f32, stops: Vec<GradientStop>) -> Self {
+        RadialGradient { center, radius, focus: center, stops, spread: Spread::Pad }
+    }
+    /// The same gradient with `Spread::Repeat` instead of `Spread::Reflect`, for backends that can not reflect.
+    ///
+    /// The circle is scaled by 2 around the focus and the gradient runs through the stops forward and then backward.
+    /// Other spreads are returned unchanged.
+    pub fn reflect_as_repeat(&self) -> RadialGradient {
+        if self.spread != Spread::Reflect {
+            return self.clone();
+        }
+        RadialGradient {
+            center: self.focus + (self.center - self.focus) * 2.0,
+            radius: self.radius * 2.0,
+            focus: self.focus,
+            stops: mirrored_stops(&self.stops),
+            spread: Spread::Repeat,
+        }
+    }
+}
+
 pub enum Paint<S: Surface> {
     Solid(Rgba8),
-    Image(S::Image, Transform)
+    Image(S::Image, Transform),
+    LinearGradient(LinearGradient),
+    RadialGradient(RadialGradient),
 }
 impl<S: Surface> Clone for Paint<S> {
     #[inline]
     fn clone(&self) -> Self {
         match *self {
             Paint::Solid(color) => Paint::Solid(color),
-            Paint::Image(ref image, tr) => Paint::Image(image.clone(), tr)
+            Paint::Image(ref image, tr) => Paint::Image(image.clone(), tr),
+            Paint::LinearGradient(ref gradient) => Paint::LinearGradient(gradient.clone()),
+            Paint::RadialGradient(ref gradient) => Paint::RadialGradient(gradient.clone()),
         }
     }
 }
@@ -192,7 +988,9 @@ impl<S: Surface> fmt::Debug for Paint<S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Paint::Solid((r, g, b, a)) => write!(f, "Solid(rgba({}, {}, {}, {}))", r, g, b, a),
-            Paint::Image(_, _) => write!(f, "Image")
+            Paint::Image(_, _) => write!(f, "Image"),
+            Paint::LinearGradient(gradient) => gradient.fmt(f),
+            Paint::RadialGradient(gradient) => gradient.fmt(f),
         }
     }
 }
@@ -207,20 +1005,78 @@ impl<S: Surface> Paint<S> {
     }
 }
 
+/// Lengths of alternating dashes and gaps, starting with a dash.
+///
+/// The values are stored inline so `LineStyle` stays `Copy`,
+/// which limits a pattern to `DashArray::CAPACITY` values.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DashArray {
+    values: [f32; DashArray::CAPACITY],
+    len: usize,
+}
+impl DashArray {
+    pub const CAPACITY: usize = 16;
+
+    /// Like in SVG, a list with negative or non-finite values is invalid.
+    /// Returns `None` for those and for lists longer than `CAPACITY`.
+    pub fn new(values: &[f32]) -> Option<Self> {
+        if values.len() > DashArray::CAPACITY || values.iter().any(|&l| !(l.is_finite() && l >= 0.0)) {
+            return None;
+        }
+        let mut dashes = DashArray::default();
+        dashes.values[.. values.len()].copy_from_slice(values);
+        dashes.len = values.len();
+        Some(dashes)
+    }
+}
+impl std::ops::Deref for DashArray {
+    type Target = [f32];
+    #[inline]
+    fn deref(&self) -> &[f32] {
+        &self.values[.. self.len]
+    }
+}
+impl PartialEq for DashArray {
+    #[inline]
+    fn eq(&self, other: &DashArray) -> bool {
+        **self == **other
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct LineStyle {
     pub width: f32,
     pub cap: LineCap,
     pub join: LineJoin,
+
+    // lengths of alternating dashes and gaps. empty for a solid line.
+    pub dash_array: DashArray,
+
+    // distance into the dash pattern at which the line starts
+    pub dash_offset: f32,
 }
 impl LineStyle {
     pub fn default(width: f32) -> Self {
         LineStyle {
             width,
             cap: LineCap::Butt,
-            join: LineJoin::Miter(width)
+            join: LineJoin::Miter(width),
+            dash_array: DashArray::default(),
+            dash_offset: 0.0,
         }
     }
+    /// Use the dash pattern `dash_array`, starting `dash_offset` into it.
+    ///
+    /// The line stays solid if `dash_array` is not valid (see `DashArray::new`).
+    pub fn dashed(mut self, dash_array: &[f32], dash_offset: f32) -> Self {
+        self.dash_array = DashArray::new(dash_array).unwrap_or_default();
+        self.dash_offset = if dash_offset.is_finite() { dash_offset } else { 0.0 };
+        self
+    }
+    #[inline]
+    pub fn is_dashed(&self) -> bool {
+        self.dash_array.iter().any(|&l| l > 0.0)
+    }
 }
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum LineCap {
@@ -277,11 +1133,84 @@ impl<S: Surface> PathStyle<S> {
     }
 }
 
+/// The memory layout of the pixels passed to `Surface::texture`.
+///
+/// Channels are listed in memory order. Unless the name says otherwise, colors are not premultiplied.
+/// 16 bit channels are in native byte order, like a `&[u16]` viewed as bytes.
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum PixelFormat {
     L8,
     Rgb24,
-    Rgba32
+    Rgba32,
+    /// gray and alpha
+    La16,
+    Bgra32,
+    /// red, green and blue multiplied by alpha
+    PremultipliedRgba32,
+    /// blue, green and red multiplied by alpha.
+    /// this is what raqote's `DrawTarget` holds on little endian machines.
+    PremultipliedBgra32,
+    L16,
+    La32,
+    Rgb48,
+    Rgba64,
+}
+impl PixelFormat {
+    #[inline]
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::L8 => 1,
+            PixelFormat::La16 | PixelFormat::L16 => 2,
+            PixelFormat::Rgb24 => 3,
+            PixelFormat::Rgba32 | PixelFormat::Bgra32 | PixelFormat::PremultipliedRgba32 |
+            PixelFormat::PremultipliedBgra32 | PixelFormat::La32 => 4,
+            PixelFormat::Rgb48 => 6,
+            PixelFormat::Rgba64 => 8,
+        }
+    }
+}
+/// Returned by `Surface::push_layer` and needed to pop the layer again.
+///
+/// A surface keeps whatever it needs to finish the layer in here.
+pub struct Layer(Option<Box<dyn std::any::Any>>);
+impl Layer {
+    /// a layer without any state
+    #[inline]
+    pub fn empty() -> Layer {
+        Layer(None)
+    }
+    #[inline]
+    pub fn new<T: std::any::Any>(state: T) -> Layer {
+        Layer(Some(Box::new(state)))
+    }
+    /// the state passed to `new`, if it has the type `T`
+    #[inline]
+    pub fn into_state<T: std::any::Any>(self) -> Option<T> {
+        self.0.and_then(|state| state.downcast().ok()).map(|state| *state)
+    }
+}
+
+/// how a layer is composited onto what is below it
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
 }
+
 pub trait Surface: Sized {
     type Outline: Outline;
     type Style: Clone;
@@ -292,17 +1221,74 @@ pub trait Surface: Sized {
     fn build_style(&mut self, style: PathStyle<Self>) -> Self::Style;
     fn draw_path(&mut self, path: Self::Outline, style: &Self::Style, clip: Option<&Self::ClipPath>);
     fn clip_path(&mut self, path: Self::Outline, fill_rule: FillRule) -> Self::ClipPath;
-    fn texture(&mut self, width: u32, height: u32, data: &[u8], format: PixelFormat) -> Self::Image;
+
+    /// Create an image from `height` rows of `width` pixels, each row starting `stride` bytes after the previous one.
+    ///
+    /// Bytes between the end of a row and the start of the next one are ignored,
+    /// so this can read a part of a larger buffer.
+    /// Every `PixelFormat` is accepted; pixels are converted to whatever the backend stores.
+    fn texture_with_stride(&mut self, width: u32, height: u32, data: &[u8], stride: usize, format: PixelFormat)
+        -> Result<Self::Image, TextureError>;
+
+    /// Create an image from tightly packed rows. `data` must have exactly the length the size and format require.
+    fn texture(&mut self, width: u32, height: u32, data: &[u8], format: PixelFormat) -> Result<Self::Image, TextureError> {
+        let stride = (width as usize).checked_mul(format.bytes_per_pixel()).ok_or(TextureError::TooLarge)?;
+        pixels::check(width, height, data.len(), stride, format, true)?;
+        self.texture_with_stride(width, height, data, stride, format)
+    }
+
+    /// Everything drawn until the matching `pop_layer` is composited as one group
+    /// with the given opacity and blend mode, optionally clipped.
+    ///
+    /// The default implementation does not group anything,
+    /// so the content is drawn directly, without the opacity, blend mode and clip.
+    fn push_layer(&mut self, _opacity: f32, _blend_mode: BlendMode, _clip: Option<&Self::ClipPath>) -> Layer {
+        Layer::empty()
+    }
+    fn pop_layer(&mut self, _layer: Layer) {}
 }
 
-#[cfg(feature = "impl_raquote")]
+mod geom;
+mod pixels;
+pub use pixels::{TextureError, convert_pixels};
+mod png;
+mod raster;
+pub use raster::RasterOutput;
+pub mod flatten;
+pub mod stroke;
+pub mod boolean;
+pub use boolean::BooleanOp;
+pub mod hit_test;
+pub mod measure;
+pub use measure::{PathMeasure, ContourMeasure};
+
+pub mod path;
+pub use path::Path;
+
+mod record;
+pub use record::{Recording, StyleId, ClipId, ImageId};
+
+mod svg_path;
+pub use svg_path::{parse_path_data, PathDataError, PathDataErrorKind};
+
+mod xml;
+mod svg_import;
+pub use svg_import::{SvgDocument, SvgError};
+
+#[cfg(feature = "impl_raqote")]
 mod impl_raqote;
 
 #[cfg(feature = "impl_svg")]
 mod impl_svg;
 
+#[cfg(feature = "impl_pdf")]
+mod impl_pdf;
+
 #[cfg(feature = "impl_pathfinder")]
 mod impl_pathfinder;
 
 #[cfg(feature = "impl_svg")]
 pub use impl_svg::Svg;
+
+#[cfg(feature = "impl_pdf")]
+pub use impl_pdf::Pdf;
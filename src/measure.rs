@@ -0,0 +1,276 @@
+//! Arc length parameterization of outlines.
+//!
+//! Each curve is sampled like it would be flattened with the given tolerance.
+//! The length between two samples is integrated with Gauss-Legendre quadrature,
+//! and distances are mapped to curve parameters by interpolating between the samples.
+//! Positions, tangents and extracted pieces are then computed on the exact curves.
+
+use crate::{Outline, PathBuilder, ErrorPolicy, PathEvent, Vector};
+use crate::path::Segment;
+use crate::flatten::{quadratic_segments, cubic_segments};
+use crate::geom::{
+    quadratic_point, cubic_point, quadratic_derivative, cubic_derivative,
+    quadratic_blossom, cubic_blossom
+};
+
+struct MeasuredSegment {
+    from: Vector,
+    segment: Segment,
+    // distance from the start of the contour to the start of the segment
+    offset: f32,
+    // (t, distance from the start of the segment), starting at (0, 0)
+    samples: Vec<(f32, f32)>,
+}
+impl MeasuredSegment {
+    fn new(from: Vector, segment: Segment, offset: f32, tolerance: f32) -> Self {
+        let n = match segment {
+            Segment::Line(_) => 1,
+            Segment::Quadratic(c, p) => quadratic_segments(from, c, p, tolerance),
+            Segment::Cubic(c1, c2, p) => cubic_segments(from, c1, c2, p, tolerance),
+        };
+        let mut samples = Vec::with_capacity(n + 1);
+        samples.push((0.0, 0.0));
+        let mut distance = 0.0;
+        for i in 1 ..= n {
+            let (t0, t1) = ((i - 1) as f32 / n as f32, i as f32 / n as f32);
+            distance += arc_length(from, segment, t0, t1);
+            samples.push((t1, distance));
+        }
+        MeasuredSegment { from, segment, offset, samples }
+    }
+    #[inline]
+    fn length(&self) -> f32 {
+        self.samples.last().unwrap().1
+    }
+    // the curve parameter at `distance` from the start of the segment
+    fn parameter(&self, distance: f32) -> f32 {
+        let i = self.samples.iter().position(|&(_, d)| d >= distance).unwrap_or(self.samples.len() - 1).max(1);
+        let (t0, d0) = self.samples[i - 1];
+        let (t1, d1) = self.samples[i];
+        if d1 > d0 {
+            t0 + (t1 - t0) * ((distance - d0) / (d1 - d0)).clamp(0.0, 1.0)
+        } else {
+            t1
+        }
+    }
+    fn tangent(&self, t: f32) -> Vector {
+        let from = self.from;
+        let d = derivative(from, self.segment, t);
+        // the derivative vanishes where control points coincide with the end points
+        let d = if d.length() > 0.0 { d } else { self.segment.end() - from };
+        if d.length() > 0.0 { d * (1.0 / d.length()) } else { d }
+    }
+    // the part between the parameters t0 and t1
+    fn part(&self, t0: f32, t1: f32) -> Segment {
+        let from = self.from;
+        match self.segment {
+            Segment::Line(p) => Segment::Line(from.lerp(p, t1)),
+            Segment::Quadratic(c, p) => Segment::Quadratic(
+                quadratic_blossom(from, c, p, t0, t1),
+                quadratic_point(from, c, p, t1)
+            ),
+            Segment::Cubic(c1, c2, p) => Segment::Cubic(
+                cubic_blossom(from, c1, c2, p, t0, t0, t1),
+                cubic_blossom(from, c1, c2, p, t0, t1, t1),
+                cubic_point(from, c1, c2, p, t1)
+            ),
+        }
+    }
+}
+
+#[inline]
+fn point(from: Vector, segment: Segment, t: f32) -> Vector {
+    match segment {
+        Segment::Line(p) => from.lerp(p, t),
+        Segment::Quadratic(c, p) => quadratic_point(from, c, p, t),
+        Segment::Cubic(c1, c2, p) => cubic_point(from, c1, c2, p, t),
+    }
+}
+
+#[inline]
+fn derivative(from: Vector, segment: Segment, t: f32) -> Vector {
+    match segment {
+        Segment::Line(p) => p - from,
+        Segment::Quadratic(c, p) => quadratic_derivative(from, c, p, t),
+        Segment::Cubic(c1, c2, p) => cubic_derivative(from, c1, c2, p, t),
+    }
+}
+
+// the length of the curve between the parameters t0 and t1.
+// three point Gauss-Legendre quadrature of the speed, which is exact for lines.
+fn arc_length(from: Vector, segment: Segment, t0: f32, t1: f32) -> f32 {
+    const NODES: [(f32, f32); 3] = [(-0.774_596_7, 5.0 / 9.0), (0.0, 8.0 / 9.0), (0.774_596_7, 5.0 / 9.0)];
+    let (mid, half) = (0.5 * (t0 + t1), 0.5 * (t1 - t0));
+    let sum: f32 = NODES.iter().map(|&(x, w)| w * derivative(from, segment, mid + half * x).length()).sum();
+    sum * half
+}
+
+fn add_segment<O: Outline>(builder: &mut PathBuilder<O>, segment: Segment) {
+    match segment {
+        Segment::Line(p) => builder.line_to(p),
+        Segment::Quadratic(c, p) => builder.quadratic_curve_to(c, p),
+        Segment::Cubic(c1, c2, p) => builder.cubic_curve_to(c1, c2, p),
+    }
+}
+
+/// The measured length of one contour.
+///
+/// The closing line of a closed contour counts towards the length.
+pub struct ContourMeasure {
+    start: Vector,
+    segments: Vec<MeasuredSegment>,
+    length: f32,
+    closed: bool,
+}
+impl ContourMeasure {
+    fn new(start: Vector) -> Self {
+        ContourMeasure { start, segments: vec![], length: 0.0, closed: false }
+    }
+    fn push(&mut self, segment: Segment, tolerance: f32) {
+        let from = self.segments.last().map(|s| s.segment.end()).unwrap_or(self.start);
+        let measured = MeasuredSegment::new(from, segment, self.length, tolerance);
+        self.length += measured.length();
+        self.segments.push(measured);
+    }
+    fn close(&mut self, tolerance: f32) {
+        let end = self.segments.last().map(|s| s.segment.end()).unwrap_or(self.start);
+        if end != self.start {
+            self.push(Segment::Line(self.start), tolerance);
+        }
+        self.closed = true;
+    }
+
+    #[inline]
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    // the index of the segment and the curve parameter at `distance`, clamped to the contour
+    fn locate(&self, distance: f32) -> Option<(usize, f32)> {
+        let distance = distance.clamp(0.0, self.length);
+        let i = self.segments.iter()
+            .position(|s| s.offset + s.length() >= distance)
+            .unwrap_or(self.segments.len().checked_sub(1)?);
+        let segment = &self.segments[i];
+        Some((i, segment.parameter(distance - segment.offset)))
+    }
+
+    /// The position and unit tangent at `distance` from the start of the contour.
+    ///
+    /// Distances outside of the contour are clamped to its ends.
+    /// Returns `None` if the contour has no segments.
+    pub fn position_and_tangent(&self, distance: f32) -> Option<(Vector, Vector)> {
+        let (i, t) = self.locate(distance)?;
+        let segment = &self.segments[i];
+        Some((point(segment.from, segment.segment, t), segment.tangent(t)))
+    }
+
+    /// Append the part of the contour between the distances `start` and `end` to `builder`.
+    ///
+    /// The part starts with a `move_to`. Nothing is added if `start >= end`.
+    pub fn extract<O: Outline>(&self, start: f32, end: f32, builder: &mut PathBuilder<O>) {
+        let start = start.max(0.0);
+        let end = end.min(self.length);
+        if start >= end {
+            return;
+        }
+        let (first_index, t0) = match self.locate(start) {
+            Some(l) => l,
+            None => return
+        };
+        let (last_index, t1) = self.locate(end).unwrap();
+        let (first, last) = (&self.segments[first_index], &self.segments[last_index]);
+        builder.move_to(point(first.from, first.segment, t0));
+
+        if first_index == last_index {
+            add_segment(builder, first.part(t0, t1));
+            return;
+        }
+        if t0 < 1.0 {
+            add_segment(builder, first.part(t0, 1.0));
+        }
+        for s in &self.segments[first_index + 1 .. last_index] {
+            add_segment(builder, s.segment);
+        }
+        add_segment(builder, last.part(0.0, t1));
+    }
+}
+
+/// Lengths, positions and tangents along all contours of an outline.
+///
+/// Distances for the whole outline run through the contours in order.
+pub struct PathMeasure {
+    contours: Vec<ContourMeasure>,
+    length: f32,
+}
+impl PathMeasure {
+    /// Measure `outline`. Curves are sampled as if they were flattened to within `tolerance`.
+    pub fn new(outline: &impl Outline, tolerance: f32) -> Self {
+        let mut contours: Vec<ContourMeasure> = vec![];
+        for event in outline.events() {
+            if let PathEvent::MoveTo(p) = event {
+                contours.push(ContourMeasure::new(p));
+                continue;
+            }
+            let contour = match contours.last_mut() {
+                Some(contour) => contour,
+                None => continue
+            };
+            match event {
+                PathEvent::MoveTo(_) => unreachable!(),
+                PathEvent::LineTo(p) => contour.push(Segment::Line(p), tolerance),
+                PathEvent::QuadTo(c, p) => contour.push(Segment::Quadratic(c, p), tolerance),
+                PathEvent::CubicTo(c1, c2, p) => contour.push(Segment::Cubic(c1, c2, p), tolerance),
+                PathEvent::Close => contour.close(tolerance),
+            }
+        }
+        let length = contours.iter().map(|c| c.length).sum();
+        PathMeasure { contours, length }
+    }
+
+    #[inline]
+    pub fn contours(&self) -> &[ContourMeasure] {
+        &self.contours
+    }
+
+    /// the sum of the lengths of all contours
+    #[inline]
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// The position and unit tangent at `distance` from the start of the outline.
+    ///
+    /// Distances outside of the outline are clamped to its ends.
+    pub fn position_and_tangent(&self, distance: f32) -> Option<(Vector, Vector)> {
+        let mut distance = distance.max(0.0);
+        let mut last = None;
+        for contour in self.contours.iter().filter(|c| !c.segments.is_empty()) {
+            if distance <= contour.length {
+                return contour.position_and_tangent(distance);
+            }
+            distance -= contour.length;
+            last = Some(contour);
+        }
+        last.and_then(|c| c.position_and_tangent(c.length))
+    }
+
+    /// The part of the outline between the distances `start` and `end`.
+    ///
+    /// Each contour that is touched becomes a separate open contour.
+    pub fn extract<O: Outline>(&self, start: f32, end: f32) -> O {
+        let mut builder = PathBuilder::with_policy(ErrorPolicy::Ignore);
+        let mut offset = 0.0;
+        for contour in &self.contours {
+            if end > offset && start < offset + contour.length {
+                contour.extract(start - offset, end - offset, &mut builder);
+            }
+            offset += contour.length;
+        }
+        builder.into_outline()
+    }
+}
@@ -0,0 +1,228 @@
+//! A lightweight outline type that does not depend on any backend.
+//!
+//! `Path` implements both `Outline` and `Contour` (like raqote's `Path`),
+//! so it can be built with a `PathBuilder` and used to cache or process geometry
+//! independently of the surface that finally renders it.
+
+use crate::{Contour, Outline, Vector, Transform, Rect, PathEvent, arc_to_cubics};
+use crate::geom::{quadratic_point, cubic_point, quadratic_roots};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Segment {
+    Line(Vector),
+    Quadratic(Vector, Vector),
+    Cubic(Vector, Vector, Vector),
+}
+impl Segment {
+    /// the point the segment ends in
+    #[inline]
+    pub fn end(&self) -> Vector {
+        match *self {
+            Segment::Line(p) | Segment::Quadratic(_, p) | Segment::Cubic(_, _, p) => p
+        }
+    }
+    #[inline]
+    pub fn transform(self, tr: Transform) -> Segment {
+        match self {
+            Segment::Line(p) => Segment::Line(tr * p),
+            Segment::Quadratic(c, p) => Segment::Quadratic(tr * c, tr * p),
+            Segment::Cubic(c1, c2, p) => Segment::Cubic(tr * c1, tr * c2, tr * p),
+        }
+    }
+    #[inline]
+    fn event(&self) -> PathEvent {
+        match *self {
+            Segment::Line(p) => PathEvent::LineTo(p),
+            Segment::Quadratic(c, p) => PathEvent::QuadTo(c, p),
+            Segment::Cubic(c1, c2, p) => PathEvent::CubicTo(c1, c2, p),
+        }
+    }
+
+    // extend (min, max) by the exact bounds of the segment starting at `from`
+    fn extend_bounds(&self, from: Vector, min: &mut Vector, max: &mut Vector) {
+        let mut add = |p: Vector| {
+            *min = min.min(p);
+            *max = max.max(p);
+        };
+        add(self.end());
+        match *self {
+            Segment::Line(_) => {}
+            Segment::Quadratic(c, p) => {
+                // the derivative is linear: 2(1-t)(c - from) + 2t(p - c)
+                let d = from - c * 2.0 + p;
+                for &(a, d) in &[(from.x() - c.x(), d.x()), (from.y() - c.y(), d.y())] {
+                    if d != 0.0 {
+                        let t = a / d;
+                        if t > 0.0 && t < 1.0 {
+                            add(quadratic_point(from, c, p, t));
+                        }
+                    }
+                }
+            }
+            Segment::Cubic(c1, c2, p) => {
+                // roots of the derivative a t² + b t + c
+                let a = (c1 - c2) * 3.0 + p - from;
+                let b = (from - c1 * 2.0 + c2) * 2.0;
+                let c = c1 - from;
+                for &(a, b, c) in &[(a.x(), b.x(), c.x()), (a.y(), b.y(), c.y())] {
+                    for t in quadratic_roots(a, b, c).iter().flatten() {
+                        if *t > 0.0 && *t < 1.0 {
+                            add(cubic_point(from, c1, c2, p, *t));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// a connected sequence of segments starting at `start`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubPath {
+    pub start: Vector,
+    pub segments: Vec<Segment>,
+    pub closed: bool,
+}
+impl SubPath {
+    #[inline]
+    pub fn new(start: Vector) -> Self {
+        SubPath { start, segments: vec![], closed: false }
+    }
+    /// the current point of the sub path
+    #[inline]
+    pub fn end(&self) -> Vector {
+        self.segments.last().map(|s| s.end()).unwrap_or(self.start)
+    }
+    /// the exact bounds of the curves, without the control points
+    pub fn bounds(&self) -> Rect {
+        let (mut min, mut max) = (self.start, self.start);
+        let mut from = self.start;
+        for segment in &self.segments {
+            segment.extend_bounds(from, &mut min, &mut max);
+            from = segment.end();
+        }
+        Rect::from_points(min, max)
+    }
+    pub fn transform(self, tr: Transform) -> SubPath {
+        SubPath {
+            start: tr * self.start,
+            segments: self.segments.into_iter().map(|s| s.transform(tr)).collect(),
+            closed: self.closed,
+        }
+    }
+    pub fn events<'a>(&'a self) -> impl Iterator<Item=PathEvent> + 'a {
+        let close = if self.closed { Some(PathEvent::Close) } else { None };
+        Some(PathEvent::MoveTo(self.start)).into_iter()
+            .chain(self.segments.iter().map(Segment::event))
+            .chain(close)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Path {
+    pub contours: Vec<SubPath>,
+}
+impl Path {
+    #[inline]
+    pub fn new() -> Self {
+        Path { contours: vec![] }
+    }
+    #[inline]
+    pub fn contours(&self) -> &[SubPath] {
+        &self.contours
+    }
+
+    // the sub path to append segments to.
+    // drawing after `close` continues from the start of the closed sub path.
+    fn current(&mut self, p: Vector) -> &mut SubPath {
+        let start = match self.contours.last() {
+            None => Some(p),
+            Some(sub) if sub.closed => Some(sub.start),
+            Some(_) => None
+        };
+        if let Some(start) = start {
+            self.contours.push(SubPath::new(start));
+        }
+        self.contours.last_mut().unwrap()
+    }
+}
+
+impl Contour for Path {
+    #[inline]
+    fn new() -> Self {
+        Path::new()
+    }
+    #[inline]
+    fn move_to(&mut self, p: Vector) {
+        self.contours.push(SubPath::new(p));
+    }
+    #[inline]
+    fn line_to(&mut self, p: Vector) {
+        self.current(p).segments.push(Segment::Line(p));
+    }
+    #[inline]
+    fn quadratic_curve_to(&mut self, c: Vector, p: Vector) {
+        self.current(p).segments.push(Segment::Quadratic(c, p));
+    }
+    #[inline]
+    fn cubic_curve_to(&mut self, c1: Vector, c2: Vector, p: Vector) {
+        self.current(p).segments.push(Segment::Cubic(c1, c2, p));
+    }
+    fn arc(&mut self, transform: Transform, start_angle: f32, end_angle: f32, clockwise: bool) {
+        let (start, curves) = arc_to_cubics(transform, start_angle, end_angle, clockwise);
+        let sub = self.current(start);
+        if sub.end() != start {
+            sub.segments.push(Segment::Line(start));
+        }
+        sub.segments.extend(curves.map(|(c1, c2, p)| Segment::Cubic(c1, c2, p)));
+    }
+    #[inline]
+    fn close(&mut self) {
+        if let Some(sub) = self.contours.last_mut() {
+            sub.closed = true;
+        }
+    }
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.contours.is_empty()
+    }
+    #[inline]
+    fn clear(&mut self) {
+        self.contours.clear();
+    }
+}
+
+impl Outline for Path {
+    type Contour = Path;
+
+    #[inline]
+    fn empty() -> Self {
+        Path::new()
+    }
+    #[inline]
+    fn add_contour(&mut self, contour: Path) {
+        self.contours.extend(contour.contours);
+    }
+    #[inline]
+    fn add_outline(&mut self, outline: Path) {
+        self.contours.extend(outline.contours);
+    }
+    fn bounding_box(&self) -> Option<Rect> {
+        self.contours.iter().map(SubPath::bounds).fold(None, |acc, r| match acc {
+            None => Some(r),
+            Some(acc) => Some(r.union_rect(acc))
+        })
+    }
+    fn transform(self, transform: Transform) -> Self {
+        Path {
+            contours: self.contours.into_iter().map(|c| c.transform(transform)).collect()
+        }
+    }
+    #[inline]
+    fn clear(&mut self) {
+        self.contours.clear();
+    }
+    fn events<'a>(&'a self) -> Box<dyn Iterator<Item=PathEvent> + 'a> {
+        Box::new(self.contours.iter().flat_map(SubPath::events))
+    }
+}
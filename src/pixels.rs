@@ -0,0 +1,120 @@
+//! Validation and conversion of pixel buffers passed to `Surface::texture`.
+
+use crate::{PixelFormat, Rgba8};
+use std::fmt;
+use std::error::Error;
+
+/// Why `Surface::texture` could not create an image.
+///
+/// Every backend converts every `PixelFormat`, so the format itself is never an error.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextureError {
+    /// the width or height is zero
+    ZeroSize,
+    /// the size of the image does not fit into memory
+    TooLarge,
+    /// the stride is shorter than one row of pixels
+    StrideTooSmall { stride: usize, row: usize },
+    /// the buffer does not have the length the size, stride and format require
+    WrongLength { expected: usize, actual: usize },
+}
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TextureError::ZeroSize => write!(f, "the texture has no pixels"),
+            TextureError::TooLarge => write!(f, "the texture is too large"),
+            TextureError::StrideTooSmall { stride, row } =>
+                write!(f, "the stride of {} bytes is shorter than a row of {} bytes", stride, row),
+            TextureError::WrongLength { expected, actual } =>
+                write!(f, "expected {} bytes of pixel data, got {}", expected, actual),
+        }
+    }
+}
+impl Error for TextureError {}
+
+/// Check that a buffer of `len` bytes holds `height` rows of `width` pixels, `stride` bytes apart.
+///
+/// With `exact`, the buffer must not have trailing bytes after the last row.
+/// Returns the length of a row in bytes.
+pub(crate) fn check(width: u32, height: u32, len: usize, stride: usize, format: PixelFormat, exact: bool)
+    -> Result<usize, TextureError>
+{
+    if width == 0 || height == 0 {
+        return Err(TextureError::ZeroSize);
+    }
+    let row = (width as usize).checked_mul(format.bytes_per_pixel()).ok_or(TextureError::TooLarge)?;
+    if stride < row {
+        return Err(TextureError::StrideTooSmall { stride, row });
+    }
+    let expected = stride.checked_mul(height as usize - 1)
+        .and_then(|n| n.checked_add(row))
+        .ok_or(TextureError::TooLarge)?;
+    if len < expected || (exact && len != expected) {
+        return Err(TextureError::WrongLength { expected, actual: len });
+    }
+    Ok(row)
+}
+
+/// The rows of pixels in `data`, without the padding at their end. See `check`.
+pub(crate) fn rows(width: u32, height: u32, data: &[u8], stride: usize, format: PixelFormat)
+    -> Result<impl Iterator<Item=&[u8]>, TextureError>
+{
+    let row = check(width, height, data.len(), stride, format, false)?;
+    Ok((0 .. height as usize).map(move |y| &data[y * stride .. y * stride + row]))
+}
+
+// scale a 16 bit channel down to 8 bits
+#[inline]
+fn narrow(bytes: &[u8]) -> u8 {
+    let v = u16::from_ne_bytes([bytes[0], bytes[1]]) as u32;
+    ((v * 255 + 32767) / 65535) as u8
+}
+
+#[inline]
+pub(crate) fn unpremultiply(r: u8, g: u8, b: u8, a: u8) -> Rgba8 {
+    if a == 0 {
+        return (0, 0, 0, 0);
+    }
+    let c = |v: u8| ((v as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8;
+    (c(r), c(g), c(b), a)
+}
+
+/// Convert one row of pixels into straight (not premultiplied) 8 bit RGBA.
+pub(crate) fn rgba8(row: &[u8], format: PixelFormat) -> impl Iterator<Item=Rgba8> + '_ {
+    row.chunks_exact(format.bytes_per_pixel()).map(move |p| match format {
+        PixelFormat::L8 => (p[0], p[0], p[0], 255),
+        PixelFormat::Rgb24 => (p[0], p[1], p[2], 255),
+        PixelFormat::Rgba32 => (p[0], p[1], p[2], p[3]),
+        PixelFormat::La16 => (p[0], p[0], p[0], p[1]),
+        PixelFormat::Bgra32 => (p[2], p[1], p[0], p[3]),
+        PixelFormat::PremultipliedRgba32 => unpremultiply(p[0], p[1], p[2], p[3]),
+        PixelFormat::PremultipliedBgra32 => unpremultiply(p[2], p[1], p[0], p[3]),
+        PixelFormat::L16 => {
+            let l = narrow(&p[0 .. 2]);
+            (l, l, l, 255)
+        }
+        PixelFormat::La32 => {
+            let l = narrow(&p[0 .. 2]);
+            (l, l, l, narrow(&p[2 .. 4]))
+        }
+        PixelFormat::Rgb48 => (narrow(&p[0 .. 2]), narrow(&p[2 .. 4]), narrow(&p[4 .. 6]), 255),
+        PixelFormat::Rgba64 => (narrow(&p[0 .. 2]), narrow(&p[2 .. 4]), narrow(&p[4 .. 6]), narrow(&p[6 .. 8])),
+    })
+}
+
+/// Convert pixels in any format into tightly packed, straight 8 bit RGBA (`PixelFormat::Rgba32`).
+///
+/// The buffer is validated like in `Surface::texture_with_stride`.
+pub fn convert_pixels(width: u32, height: u32, data: &[u8], stride: usize, format: PixelFormat) -> Result<Vec<u8>, TextureError> {
+    let rows = rows(width, height, data, stride, format)?;
+    let len = (width as usize).checked_mul(height as usize)
+        .and_then(|n| n.checked_mul(4))
+        .ok_or(TextureError::TooLarge)?;
+    let mut out = Vec::with_capacity(len);
+    for row in rows {
+        for (r, g, b, a) in rgba8(row, format) {
+            out.extend_from_slice(&[r, g, b, a]);
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,126 @@
+//! A minimal PNG encoder.
+//!
+//! The image data is stored in uncompressed deflate blocks, so the output is larger than it
+//! needs to be, but it is valid PNG that every decoder understands and needs no extra dependency.
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ColorType {
+    Gray = 0,
+    Rgb = 2,
+    GrayAlpha = 4,
+    Rgba = 6,
+}
+impl ColorType {
+    #[inline]
+    pub fn channels(self) -> usize {
+        match self {
+            ColorType::Gray => 1,
+            ColorType::GrayAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        }
+    }
+}
+
+/// Encode `data` (rows of `width` pixels, 8 bits per channel, no padding) as PNG.
+pub fn encode(width: u32, height: u32, color_type: ColorType, data: &[u8]) -> Vec<u8> {
+    let stride = width as usize * color_type.channels();
+    assert_eq!(data.len(), stride * height as usize);
+
+    let mut out = Vec::with_capacity(data.len() + height as usize + 1024);
+    out.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+
+    let mut ihdr = [0; 13];
+    ihdr[0 .. 4].copy_from_slice(&width.to_be_bytes());
+    ihdr[4 .. 8].copy_from_slice(&height.to_be_bytes());
+    ihdr[8] = 8; // bit depth
+    ihdr[9] = color_type as u8;
+    // compression, filter and interlace method are all 0
+    chunk(&mut out, b"IHDR", &ihdr);
+
+    // every row starts with the filter type (0 = None)
+    let mut raw = Vec::with_capacity(data.len() + height as usize);
+    if stride > 0 {
+        for row in data.chunks(stride) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+    }
+    chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start ..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// zlib stream consisting of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xFFFF;
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 16);
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        // a single, empty final block
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+// the lookup table of the CRC-32 polynomial used by PNG
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc = CRC_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the largest n such that the sums can't overflow before the modulo
+    for block in data.chunks(5552) {
+        for &x in block {
+            a += x as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
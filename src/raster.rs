@@ -0,0 +1,48 @@
+//! Encoding the pixels of raster surfaces into image files.
+
+use crate::png::{self, ColorType};
+
+/// A surface that renders into pixels, which can be saved as PNG, PPM or PAM.
+pub trait RasterOutput {
+    /// The width and height in pixels and the pixels as straight (not premultiplied) 8 bit RGBA.
+    fn to_rgba8(&self) -> (u32, u32, Vec<u8>);
+
+    /// Encode as PNG, dropping the alpha or color channels if they are not needed.
+    ///
+    /// The image data is not compressed (it is stored in uncompressed deflate blocks),
+    /// so the file takes a little more than the bytes of the pixels it keeps,
+    /// about 4 MB for a 1000×1000 RGBA image. Recompress it with an image tool if size matters.
+    fn encode_png(&self) -> Vec<u8> {
+        let (width, height, data) = self.to_rgba8();
+        let pixels = data.chunks_exact(4);
+        let opaque = pixels.clone().all(|p| p[3] == 255);
+        let gray = pixels.clone().all(|p| p[0] == p[1] && p[1] == p[2]);
+        let (color_type, channels): (ColorType, &[usize]) = match (gray, opaque) {
+            (true, true) => (ColorType::Gray, &[0]),
+            (true, false) => (ColorType::GrayAlpha, &[0, 3]),
+            (false, true) => (ColorType::Rgb, &[0, 1, 2]),
+            (false, false) => (ColorType::Rgba, &[0, 1, 2, 3]),
+        };
+        let packed: Vec<u8> = pixels.flat_map(|p| channels.iter().map(move |&c| p[c])).collect();
+        png::encode(width, height, color_type, &packed)
+    }
+
+    /// Encode as binary PPM (P6). The alpha channel is dropped.
+    fn encode_ppm(&self) -> Vec<u8> {
+        let (width, height, data) = self.to_rgba8();
+        let mut out = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        out.extend(data.chunks_exact(4).flat_map(|p| p[.. 3].iter().cloned()));
+        out
+    }
+
+    /// Encode as PAM (P7) with the `RGB_ALPHA` tuple type.
+    fn encode_pam(&self) -> Vec<u8> {
+        let (width, height, data) = self.to_rgba8();
+        let mut out = format!(
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+            width, height
+        ).into_bytes();
+        out.extend_from_slice(&data);
+        out
+    }
+}
@@ -0,0 +1,192 @@
+//! A `Surface` that records a display list, which can be replayed onto any other `Surface`.
+
+use crate::{Surface, Outline, Path, PathStyle, Paint, FillRule, PixelFormat, BlendMode, Layer, Vector, TextureError, pixels};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// distinguishes the ids of different recordings
+static NEXT_RECORDING: AtomicUsize = AtomicUsize::new(0);
+
+/// A style of a `Recording`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StyleId {
+    recording: usize,
+    index: usize,
+}
+
+/// A clip path of a `Recording`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClipId {
+    recording: usize,
+    index: usize,
+}
+
+/// An image of a `Recording`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ImageId {
+    recording: usize,
+    index: usize,
+}
+
+struct Texture {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+    format: PixelFormat,
+}
+
+// indices into the styles and clips, checked when recorded
+enum Command {
+    Draw(Path, usize, Option<usize>),
+    PushLayer(f32, BlendMode, Option<usize>),
+    PopLayer,
+}
+
+/// Records all calls and replays them with `replay`.
+///
+/// Styles, clip paths and images are referred to by ids that only this recording hands out,
+/// and translated into the corresponding objects of the target surface when replaying.
+/// Ids of another recording are ignored with a warning: paths drawn with such a style are skipped,
+/// such clips and image paints are left out.
+pub struct Recording {
+    id: usize,
+    size: Vector,
+    styles: Vec<PathStyle<Recording>>,
+    clips: Vec<(Path, FillRule)>,
+    textures: Vec<Texture>,
+    commands: Vec<Command>,
+}
+impl Recording {
+    #[inline]
+    pub fn size(&self) -> Vector {
+        self.size
+    }
+
+    // the index of an id, if it was created by this recording
+    fn style(&self, id: &StyleId) -> Option<usize> {
+        if id.recording == self.id { Some(id.index) } else { None }
+    }
+    fn clip(&self, id: Option<&ClipId>) -> Option<usize> {
+        let id = id?;
+        if id.recording == self.id {
+            Some(id.index)
+        } else {
+            warn!("clip path of another recording, drawing without a clip");
+            None
+        }
+    }
+    fn paint(&self, paint: Option<Paint<Self>>) -> Option<Paint<Self>> {
+        match paint {
+            Some(Paint::Image(id, _)) if id.recording != self.id => {
+                warn!("image of another recording, leaving out the paint");
+                None
+            }
+            paint => paint
+        }
+    }
+
+    /// Draw everything that was recorded onto `surface`.
+    ///
+    /// Fails without drawing anything if `surface` can not create one of the recorded images.
+    pub fn replay<S: Surface>(&self, surface: &mut S) -> Result<(), TextureError> {
+        let images: Vec<S::Image> = self.textures.iter()
+            .map(|t| surface.texture(t.width, t.height, &t.data, t.format))
+            .collect::<Result<_, _>>()?;
+        let clips: Vec<S::ClipPath> = self.clips.iter()
+            .map(|&(ref path, fill_rule)| surface.clip_path(path.to_outline(), fill_rule))
+            .collect();
+        let styles: Vec<S::Style> = self.styles.iter()
+            .map(|style| surface.build_style(PathStyle {
+                fill: style.fill.as_ref().map(|p| paint(p, &images)),
+                stroke: style.stroke.as_ref().map(|(p, line)| (paint(p, &images), *line)),
+                fill_rule: style.fill_rule
+            }))
+            .collect();
+
+        let mut layers = Vec::new();
+        for command in &self.commands {
+            match *command {
+                Command::Draw(ref path, style, clip) => {
+                    surface.draw_path(path.to_outline(), &styles[style], clip.map(|c| &clips[c]));
+                }
+                Command::PushLayer(opacity, blend_mode, clip) => {
+                    layers.push(surface.push_layer(opacity, blend_mode, clip.map(|c| &clips[c])));
+                }
+                Command::PopLayer => {
+                    if let Some(layer) = layers.pop() {
+                        surface.pop_layer(layer);
+                    }
+                }
+            }
+        }
+        // close layers that were never popped
+        while let Some(layer) = layers.pop() {
+            surface.pop_layer(layer);
+        }
+        Ok(())
+    }
+}
+
+fn paint<S: Surface>(paint: &Paint<Recording>, images: &[S::Image]) -> Paint<S> {
+    match *paint {
+        Paint::Solid(color) => Paint::Solid(color),
+        Paint::Image(id, tr) => Paint::Image(images[id.index].clone(), tr),
+        Paint::LinearGradient(ref gradient) => Paint::LinearGradient(gradient.clone()),
+        Paint::RadialGradient(ref gradient) => Paint::RadialGradient(gradient.clone()),
+    }
+}
+
+impl Surface for Recording {
+    type Outline = Path;
+    type Style = StyleId;
+    type ClipPath = ClipId;
+    type Image = ImageId;
+
+    fn new(size: Vector) -> Self {
+        Recording {
+            id: NEXT_RECORDING.fetch_add(1, Ordering::Relaxed),
+            size,
+            styles: vec![],
+            clips: vec![],
+            textures: vec![],
+            commands: vec![],
+        }
+    }
+    fn build_style(&mut self, style: PathStyle<Self>) -> StyleId {
+        let style = PathStyle {
+            fill: self.paint(style.fill),
+            stroke: style.stroke.and_then(|(paint, line)| Some((self.paint(Some(paint))?, line))),
+            fill_rule: style.fill_rule
+        };
+        self.styles.push(style);
+        StyleId { recording: self.id, index: self.styles.len() - 1 }
+    }
+    fn draw_path(&mut self, path: Path, style: &StyleId, clip: Option<&ClipId>) {
+        match self.style(style) {
+            Some(style) => {
+                let clip = self.clip(clip);
+                self.commands.push(Command::Draw(path, style, clip));
+            }
+            None => warn!("style of another recording, skipping the path")
+        }
+    }
+    fn clip_path(&mut self, path: Path, fill_rule: FillRule) -> ClipId {
+        self.clips.push((path, fill_rule));
+        ClipId { recording: self.id, index: self.clips.len() - 1 }
+    }
+    fn texture_with_stride(&mut self, width: u32, height: u32, data: &[u8], stride: usize, format: PixelFormat)
+        -> Result<ImageId, TextureError>
+    {
+        // stored tightly packed
+        let data = pixels::rows(width, height, data, stride, format)?.flatten().cloned().collect();
+        self.textures.push(Texture { width, height, data, format });
+        Ok(ImageId { recording: self.id, index: self.textures.len() - 1 })
+    }
+    fn push_layer(&mut self, opacity: f32, blend_mode: BlendMode, clip: Option<&ClipId>) -> Layer {
+        let clip = self.clip(clip);
+        self.commands.push(Command::PushLayer(opacity, blend_mode, clip));
+        Layer::empty()
+    }
+    fn pop_layer(&mut self, _layer: Layer) {
+        self.commands.push(Command::PopLayer);
+    }
+}
@@ -0,0 +1,267 @@
+//! Convert strokes into outlines that can be filled.
+//!
+//! The outline is flattened first, then each contour is offset by half the line width
+//! to both sides. Joins and caps are added as described by the `LineStyle`,
+//! round ones as curves. Overlapping parts of the result have the same orientation,
+//! so it has to be filled with `FillRule::NonZero`.
+
+use crate::{Outline, PathBuilder, ErrorPolicy, LineStyle, LineCap, LineJoin, Vector, Transform, arc_to_cubics};
+use crate::flatten::polylines;
+
+/// Turn the stroke of `outline` with the given `style` into a fill outline.
+///
+/// Curves are approximated with an error of at most `tolerance`. Dashes are applied if present.
+///
+/// # Panics
+/// If `tolerance` is not positive and finite.
+pub fn stroke_to_fill<A: Outline, B: Outline>(outline: &A, style: &LineStyle, tolerance: f32) -> B {
+    let mut builder = PathBuilder::with_policy(ErrorPolicy::Ignore);
+    if style.width.is_nan() || style.width <= 0.0 {
+        return builder.into_outline();
+    }
+    let mut stroker = Stroker { builder: &mut builder, style, half_width: 0.5 * style.width };
+
+    for polyline in polylines(outline, tolerance) {
+        let mut points = polyline.points;
+        points.dedup();
+        if polyline.closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+
+        let dashes = if style.is_dashed() {
+            dashes(&points, polyline.closed, &style.dash_array, style.dash_offset)
+        } else {
+            None
+        };
+        if let Some(dashes) = dashes {
+            for dash in dashes {
+                stroker.open(&dash);
+            }
+        } else if polyline.closed && points.len() > 2 {
+            stroker.closed(&points);
+        } else {
+            stroker.open(&points);
+        }
+    }
+    builder.into_outline()
+}
+
+// the left hand normal of the direction `d`
+#[inline]
+fn normal(d: Vector) -> Vector {
+    Vector::new(-d.y(), d.x())
+}
+#[inline]
+fn cross(a: Vector, b: Vector) -> f32 {
+    a.x() * b.y() - a.y() * b.x()
+}
+// hypot instead of `length`, which overflows for coordinates above about 1e19
+#[inline]
+fn direction(from: Vector, to: Vector) -> Vector {
+    let d = to - from;
+    d * (1.0 / d.x().hypot(d.y()))
+}
+
+struct Stroker<'a, O: Outline> {
+    builder: &'a mut PathBuilder<O>,
+    style: &'a LineStyle,
+    half_width: f32,
+}
+impl<'a, O: Outline> Stroker<'a, O> {
+    #[inline]
+    fn line_to(&mut self, p: Vector) {
+        if self.builder.pos() != Some(p) {
+            self.builder.line_to(p);
+        }
+    }
+    // arc around `center` from the direction `from` by `sweep` radians
+    fn arc(&mut self, center: Vector, from: Vector, sweep: f32) {
+        let transform = Transform::from_translation(center) * Transform::from_scale(Vector::splat(self.half_width));
+        let start = from.y().atan2(from.x());
+        let (_, curves) = arc_to_cubics(transform, start, start + sweep, sweep > 0.0);
+        for (c1, c2, p) in curves {
+            self.builder.cubic_curve_to(c1, c2, p);
+        }
+    }
+
+    // the left side around the corner `p` between segments with the directions `d0` and `d1`
+    fn join(&mut self, p: Vector, d0: Vector, d1: Vector) {
+        let hw = self.half_width;
+        let (n0, n1) = (normal(d0), normal(d1));
+        let turn = cross(d0, d1);
+        let a = p + n0 * hw;
+        let b = p + n1 * hw;
+        self.line_to(a);
+
+        if turn > 0.0 || (turn == 0.0 && d0.dot(d1) > 0.0) {
+            // the left side is on the inside. going through the center keeps the winding intact.
+            if a != b {
+                self.line_to(p);
+                self.line_to(b);
+            }
+            return;
+        }
+        match self.style.join {
+            LineJoin::Bevel => {}
+            LineJoin::Miter(limit) => {
+                let m = n0 + n1;
+                let cos_half = m.length() * 0.5;
+                // the ratio of the miter length to the line width is 1 / cos(φ/2)
+                if cos_half > 0.0 && 1.0 / cos_half <= limit {
+                    self.line_to(p + m * (hw / (2.0 * cos_half * cos_half)));
+                }
+            }
+            LineJoin::Round => {
+                let sweep = cross(n0, n1).atan2(n0.dot(n1));
+                // a reversal turns around the outside
+                let sweep = if turn == 0.0 { -std::f32::consts::PI } else { sweep };
+                self.arc(p, n0, sweep);
+            }
+        }
+        self.line_to(b);
+    }
+
+    // from the left to the right side at the end `p` of a segment with direction `d`
+    fn cap(&mut self, p: Vector, d: Vector) {
+        let hw = self.half_width;
+        let n = normal(d);
+        match self.style.cap {
+            LineCap::Butt => {}
+            LineCap::Square => {
+                self.line_to(p + (n + d) * hw);
+                self.line_to(p + (d - n) * hw);
+            }
+            LineCap::Round => self.arc(p, n, -std::f32::consts::PI),
+        }
+        self.line_to(p - n * hw);
+    }
+
+    // the left side of the points (without the first and last offset point)
+    fn side(&mut self, points: &[Vector]) {
+        for w in points.windows(3) {
+            self.join(w[1], direction(w[0], w[1]), direction(w[1], w[2]));
+        }
+    }
+
+    fn open(&mut self, points: &[Vector]) {
+        let hw = self.half_width;
+        match *points {
+            [] => {}
+            [p] => {
+                // a zero length line only shows its caps
+                if self.style.cap != LineCap::Butt {
+                    let d = Vector::new(1.0, 0.0);
+                    self.builder.move_to(p + normal(d) * hw);
+                    self.cap(p, d);
+                    self.cap(p, -d);
+                    self.builder.close();
+                }
+            }
+            [first, .., last] => {
+                let n = points.len();
+                let d_start = direction(first, points[1]);
+                let d_end = direction(points[n - 2], last);
+
+                self.builder.move_to(first + normal(d_start) * hw);
+                self.side(points);
+                self.line_to(last + normal(d_end) * hw);
+                self.cap(last, d_end);
+
+                let reversed: Vec<Vector> = points.iter().rev().cloned().collect();
+                self.side(&reversed);
+                self.line_to(first - normal(d_start) * hw);
+                self.cap(first, -d_start);
+                self.builder.close();
+            }
+        }
+    }
+
+    fn closed(&mut self, points: &[Vector]) {
+        let reversed: Vec<Vector> = points.iter().rev().cloned().collect();
+        for ring in &[points, &reversed] {
+            // wrap around so every point is a corner, including the first one
+            let last = ring[ring.len() - 1];
+            let mut corners = vec![last];
+            corners.extend_from_slice(ring);
+            corners.push(ring[0]);
+
+            self.builder.move_to(ring[0] + normal(direction(last, ring[0])) * self.half_width);
+            self.side(&corners);
+            self.builder.close();
+        }
+    }
+}
+
+/// Dash patterns that repeat more often than this on a single contour are drawn solid.
+const MAX_DASH_PERIODS: f32 = 100_000.0;
+
+// split the polyline into the dashes described by `array` and `offset`.
+// returns None if the pattern is too fine for the length of the contour.
+fn dashes(points: &[Vector], closed: bool, array: &[f32], offset: f32) -> Option<Vec<Vec<Vector>>> {
+    // an odd number of values is repeated to get an even number
+    let array: Vec<f32> = array.iter().chain(if array.len() % 2 == 1 { array } else { &[] })
+        .map(|&l| l.max(0.0))
+        .collect();
+    let period: f32 = array.iter().sum();
+    let mut segments: Vec<(Vector, Vector)> = points.windows(2).map(|w| (w[0], w[1])).collect();
+    if closed && points.len() > 1 {
+        segments.push((points[points.len() - 1], points[0]));
+    }
+    // this also guarantees progress below: some value of the pattern is larger than
+    // the rounding error of the positions along a segment
+    let total: f32 = segments.iter().map(|&(a, b)| (b - a).length()).sum();
+    let periods = total / period;
+    if periods.is_nan() || periods > MAX_DASH_PERIODS {
+        return None;
+    }
+
+    // find the position in the pattern at the start
+    let mut index = 0;
+    let mut remaining = offset % period;
+    if remaining < 0.0 {
+        remaining += period;
+    }
+    while remaining >= array[index] {
+        remaining -= array[index];
+        index = (index + 1) % array.len();
+    }
+    let mut left = array[index] - remaining;
+
+    let mut dashes = vec![];
+    let starts_with_dash = index % 2 == 0;
+    let mut dash = if starts_with_dash { points.first().map(|&p| vec![p]) } else { None };
+    for (a, b) in segments {
+        let length = (b - a).length();
+        let mut pos = 0.0;
+        while length - pos > left {
+            pos += left;
+            let p = a.lerp(b, pos / length);
+            match dash.take() {
+                Some(mut d) => {
+                    d.push(p);
+                    dashes.push(d);
+                }
+                None => dash = Some(vec![p]),
+            }
+            index = (index + 1) % array.len();
+            left = array[index];
+        }
+        left -= length - pos;
+        if let Some(ref mut d) = dash {
+            d.push(b);
+        }
+    }
+    if let Some(mut d) = dash {
+        // on a closed contour, a dash across the start point is joined instead of capped
+        if closed && starts_with_dash && !dashes.is_empty() {
+            d.extend(dashes[0].drain(1 ..));
+            dashes[0] = d;
+        } else {
+            dashes.push(d);
+        }
+    }
+    for d in &mut dashes {
+        d.dedup();
+    }
+    Some(dashes)
+}
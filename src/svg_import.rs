@@ -0,0 +1,850 @@
+//! Draws SVG documents onto any `Surface`.
+//!
+//! Supported are the basic shapes and paths, groups, nested `svg` elements and `symbol`s
+//! with transforms and view boxes, `use`, the fill and stroke properties (as attributes and in `style`),
+//! `opacity`, `mix-blend-mode`, `clipPath` and linear and radial gradients.
+//! Text, images, masks, patterns, markers and filters are ignored.
+//!
+//! Documents may come from untrusted sources, so the number of elements drawn
+//! (including every instance created by `use`) is limited.
+
+use crate::{
+    Surface, Outline, PathBuilder, ErrorPolicy, PathStyle, Paint, FillRule, LineStyle, LineCap, LineJoin, BlendMode,
+    LinearGradient, RadialGradient, GradientStop, Spread, Vector, Transform, Rect, Rgba8, Path, BooleanOp,
+    parse_path_data
+};
+use crate::boolean::boolean;
+use crate::svg_path::parse_numbers;
+use crate::xml::{self, Element, XmlError};
+use std::fmt;
+use std::error::Error;
+use std::cell::Cell;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SvgError {
+    /// the document is not well-formed XML
+    Xml(XmlError),
+    /// the root element is not `svg`
+    NotSvg,
+}
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SvgError::Xml(ref e) => write!(f, "invalid xml: {}", e),
+            SvgError::NotSvg => write!(f, "the root element is not <svg>"),
+        }
+    }
+}
+impl Error for SvgError {}
+
+// guards the recursion against deeply nested elements and `use` and `href` cycles
+const MAX_DEPTH: usize = 128;
+
+// the number of elements drawn at most, including all instances created by `use`.
+// limits the work nested `use` elements can cause, which can grow exponentially.
+const MAX_ELEMENTS: usize = 100_000;
+
+// clip paths with more than one child are combined with boolean operations, flattened to this tolerance
+const CLIP_TOLERANCE: f32 = 0.05;
+
+/// A parsed SVG document.
+pub struct SvgDocument {
+    root: Element,
+    size: Vector,
+    // the path of child indices from the root to the first element with each id
+    ids: HashMap<String, Vec<usize>>,
+}
+impl SvgDocument {
+    pub fn parse(data: &str) -> Result<SvgDocument, SvgError> {
+        let root = xml::parse(data).map_err(SvgError::Xml)?;
+        if root.local_name() != "svg" {
+            return Err(SvgError::NotSvg);
+        }
+
+        let view_box = root.attr("viewBox").map(parse_numbers).and_then(|n| match n[..] {
+            [x, y, w, h] if w > 0.0 && h > 0.0 => Some(Rect::new(Vector::new(x, y), Vector::new(w, h))),
+            _ => None
+        });
+        let width = root.attr("width").and_then(|v| length(v, 0.0));
+        let height = root.attr("height").and_then(|v| length(v, 0.0));
+        let size = match (width, height, view_box) {
+            (Some(w), Some(h), _) => Vector::new(w, h),
+            (Some(w), None, Some(vb)) => Vector::new(w, w * vb.height() / vb.width()),
+            (None, Some(h), Some(vb)) => Vector::new(h * vb.width() / vb.height(), h),
+            (None, None, Some(vb)) => vb.size(),
+            _ => Vector::new(100.0, 100.0)
+        };
+
+        let ids = index_ids(&root);
+        Ok(SvgDocument { root, size, ids })
+    }
+
+    /// the size of the viewport, suitable for `Surface::new`
+    #[inline]
+    pub fn size(&self) -> Vector {
+        self.size
+    }
+
+    /// Draw the document onto `surface`.
+    pub fn draw<S: Surface>(&self, surface: &mut S) {
+        let mut ctx = Context { doc: self, surface, budget: Cell::new(MAX_ELEMENTS) };
+        ctx.draw_element(&self.root, &State::default(), 0);
+    }
+
+    fn find(&self, id: &str) -> Option<&Element> {
+        let path = self.ids.get(id)?;
+        Some(path.iter().fold(&self.root, |element, &i| &element.children[i]))
+    }
+}
+
+// the first element with each id, in document order
+fn index_ids(root: &Element) -> HashMap<String, Vec<usize>> {
+    let mut ids = HashMap::new();
+    let mut add = |element: &Element, path: &[usize]| {
+        if let Some(id) = element.attr("id") {
+            ids.entry(id.to_owned()).or_insert_with(|| path.to_vec());
+        }
+    };
+    add(root, &[]);
+
+    // walks the tree without recursion. each element on the stack is paired with the next child to visit.
+    let mut stack: Vec<(&Element, usize)> = vec![(root, 0)];
+    let mut path: Vec<usize> = vec![];
+    while let Some((element, next)) = stack.last_mut() {
+        match element.children.get(*next) {
+            Some(child) => {
+                path.push(*next);
+                *next += 1;
+                add(child, &path);
+                stack.push((child, 0));
+            }
+            None => {
+                stack.pop();
+                path.pop();
+            }
+        }
+    }
+    ids
+}
+
+// maps the view box of a `svg` or `symbol` element into a viewport of the given size
+fn view_box_transform(element: &Element, size: Vector) -> Transform {
+    let view_box = match element.attr("viewBox").map(parse_numbers).as_deref() {
+        Some(&[x, y, w, h]) if w > 0.0 && h > 0.0 => Rect::new(Vector::new(x, y), Vector::new(w, h)),
+        _ => return Transform::from_scale(Vector::splat(1.0))
+    };
+
+    // preserveAspectRatio="[defer] <align> [meet | slice]", xMidYMid meet by default
+    let mut words = element.attr("preserveAspectRatio").unwrap_or("").split_whitespace()
+        .filter(|&w| w != "defer");
+    let align = words.next().unwrap_or("xMidYMid");
+    let slice = words.next() == Some("slice");
+
+    let scale = size / view_box.size();
+    let scale = match align {
+        "none" => scale,
+        _ if slice => Vector::splat(scale.x().max(scale.y())),
+        _ => Vector::splat(scale.x().min(scale.y())),
+    };
+    let factor = |min: &str, max: &str| if align.contains(min) { 0.0 } else if align.contains(max) { 1.0 } else { 0.5 };
+    let offset = (size - view_box.size() * scale) * Vector::new(factor("xMin", "xMax"), factor("YMin", "YMax"));
+    Transform::from_translation(offset)
+        * Transform::from_scale(scale)
+        * Transform::from_translation(-view_box.origin())
+}
+
+#[derive(Clone, Debug)]
+enum PaintSpec {
+    None,
+    Color(Rgba8),
+    CurrentColor,
+    Url(String),
+}
+
+// inherited properties
+#[derive(Clone, Debug)]
+struct State {
+    transform: Transform,
+    color: Rgba8,
+    fill: PaintSpec,
+    fill_opacity: f32,
+    fill_rule: FillRule,
+    stroke: PaintSpec,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    miter_limit: f32,
+    dash_array: Vec<f32>,
+    dash_offset: f32,
+    clip_rule: FillRule,
+    visible: bool,
+}
+impl Default for State {
+    fn default() -> State {
+        State {
+            transform: Transform::from_scale(Vector::splat(1.0)),
+            color: (0, 0, 0, 255),
+            fill: PaintSpec::Color((0, 0, 0, 255)),
+            fill_opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            stroke: PaintSpec::None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter(4.0),
+            miter_limit: 4.0,
+            dash_array: vec![],
+            dash_offset: 0.0,
+            clip_rule: FillRule::NonZero,
+            visible: true,
+        }
+    }
+}
+impl State {
+    fn set(&mut self, name: &str, value: &str) {
+        let value = value.trim();
+        if value == "inherit" {
+            return;
+        }
+        match name {
+            "color" => if let Some(c) = color(value) { self.color = c },
+            "fill" => self.fill = paint_spec(value),
+            "fill-opacity" => if let Some(o) = number(value) { self.fill_opacity = clamp(o) },
+            "fill-rule" => self.fill_rule = fill_rule(value),
+            "stroke" => self.stroke = paint_spec(value),
+            "stroke-opacity" => if let Some(o) = number(value) { self.stroke_opacity = clamp(o) },
+            "stroke-width" => if let Some(w) = length(value, 0.0) { self.stroke_width = w },
+            "stroke-linecap" => self.line_cap = match value {
+                "round" => LineCap::Round,
+                "square" => LineCap::Square,
+                _ => LineCap::Butt
+            },
+            "stroke-linejoin" => self.line_join = match value {
+                "round" => LineJoin::Round,
+                "bevel" => LineJoin::Bevel,
+                _ => LineJoin::Miter(self.miter_limit)
+            },
+            "stroke-miterlimit" => if let Some(m) = number(value) {
+                self.miter_limit = m;
+                if let LineJoin::Miter(_) = self.line_join {
+                    self.line_join = LineJoin::Miter(m);
+                }
+            },
+            "stroke-dasharray" => {
+                let mut dashes = if value == "none" { vec![] } else { parse_numbers(value) };
+                // an odd number of values is repeated once
+                if dashes.len() % 2 == 1 {
+                    let copy = dashes.clone();
+                    dashes.extend(copy);
+                }
+                self.dash_array = dashes;
+            }
+            "stroke-dashoffset" => if let Some(o) = length(value, 0.0) { self.dash_offset = o },
+            "clip-rule" => self.clip_rule = fill_rule(value),
+            "visibility" => self.visible = value == "visible",
+            _ => {}
+        }
+    }
+    // the state for the children of `element`
+    fn child(&self, element: &Element) -> State {
+        let mut state = self.clone();
+        for (name, value) in declarations(element) {
+            state.set(name, value);
+        }
+        if let Some(tr) = element.attr("transform") {
+            state.transform *= transform(tr);
+        }
+        state
+    }
+}
+
+// presentation attributes followed by the declarations in `style`, which take precedence
+fn declarations(element: &Element) -> impl Iterator<Item=(&str, &str)> {
+    let attributes = element.attributes.iter().map(|(n, v)| (n.as_str(), v.as_str()));
+    let style = element.attr("style").into_iter()
+        .flat_map(|style| style.split(';'))
+        .filter_map(|decl| {
+            let mut parts = decl.splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => Some((name.trim(), value.trim())),
+                _ => None
+            }
+        });
+    attributes.chain(style)
+}
+// the value of a non-inherited property
+fn property<'a>(element: &'a Element, name: &str) -> Option<&'a str> {
+    declarations(element).filter(|&(n, _)| n == name).map(|(_, v)| v).last()
+}
+
+fn clamp(v: f32) -> f32 {
+    v.clamp(0.0, 1.0)
+}
+fn number(s: &str) -> Option<f32> {
+    s.trim().parse().ok()
+}
+// a length or percentage of `reference`. units other than px are not supported.
+fn length(s: &str, reference: f32) -> Option<f32> {
+    let s = s.trim();
+    if let Some(percentage) = s.strip_suffix('%') {
+        return number(percentage).map(|p| p * 0.01 * reference);
+    }
+    number(s.trim_end_matches("px"))
+}
+fn attr_length(element: &Element, name: &str, reference: f32) -> f32 {
+    element.attr(name).and_then(|v| length(v, reference)).unwrap_or(0.0)
+}
+fn fill_rule(s: &str) -> FillRule {
+    match s {
+        "evenodd" => FillRule::EvenOdd,
+        _ => FillRule::NonZero
+    }
+}
+fn paint_spec(s: &str) -> PaintSpec {
+    if s == "none" {
+        PaintSpec::None
+    } else if s == "currentColor" {
+        PaintSpec::CurrentColor
+    } else if s.starts_with("url(") {
+        match s.find(')') {
+            Some(end) => PaintSpec::Url(s[4 .. end].trim().trim_matches(|c| c == '\'' || c == '"').trim_start_matches('#').into()),
+            None => PaintSpec::None
+        }
+    } else {
+        color(s).map(PaintSpec::Color).unwrap_or(PaintSpec::None)
+    }
+}
+// "url(#id)" -> "id"
+fn url(s: &str) -> Option<&str> {
+    let s = s.trim();
+    if s.starts_with("url(") && s.ends_with(')') {
+        Some(s[4 .. s.len() - 1].trim().trim_matches(|c| c == '\'' || c == '"').trim_start_matches('#'))
+    } else {
+        None
+    }
+}
+fn href(element: &Element) -> Option<&str> {
+    element.attr("href").or_else(|| element.attr("xlink:href")).map(|s| s.trim_start_matches('#'))
+}
+
+fn color(s: &str) -> Option<Rgba8> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix('#') {
+        let digit = |i: usize| u8::from_str_radix(hex.get(i .. i + 1)?, 16).ok();
+        return match hex.len() {
+            3 => Some((digit(0)? * 17, digit(1)? * 17, digit(2)? * 17, 255)),
+            6 => Some((digit(0)? * 16 + digit(1)?, digit(2)? * 16 + digit(3)?, digit(4)? * 16 + digit(5)?, 255)),
+            _ => None
+        };
+    }
+    if s.starts_with("rgb") {
+        let (open, close) = (s.find('(')?, s.rfind(')')?);
+        if open > close {
+            return None;
+        }
+        let args = &s[open + 1 .. close];
+        let parts: Vec<&str> = args.split(',').map(|p| p.trim()).collect();
+        // 0 to 255 or a percentage
+        let channel = |p: &str| length(p, 255.0).map(|c| c.clamp(0.0, 255.0).round() as u8);
+        return match parts[..] {
+            [r, g, b] => Some((channel(r)?, channel(g)?, channel(b)?, 255)),
+            [r, g, b, a] => Some((channel(r)?, channel(g)?, channel(b)?, (clamp(number(a)?) * 255.0).round() as u8)),
+            _ => None
+        };
+    }
+    let c = match s.to_ascii_lowercase().as_str() {
+        "transparent" => (0, 0, 0, 0),
+        "black" => (0, 0, 0, 255),
+        "silver" => (192, 192, 192, 255),
+        "gray" | "grey" => (128, 128, 128, 255),
+        "white" => (255, 255, 255, 255),
+        "maroon" => (128, 0, 0, 255),
+        "red" => (255, 0, 0, 255),
+        "purple" => (128, 0, 128, 255),
+        "fuchsia" | "magenta" => (255, 0, 255, 255),
+        "green" => (0, 128, 0, 255),
+        "lime" => (0, 255, 0, 255),
+        "olive" => (128, 128, 0, 255),
+        "yellow" => (255, 255, 0, 255),
+        "navy" => (0, 0, 128, 255),
+        "blue" => (0, 0, 255, 255),
+        "teal" => (0, 128, 128, 255),
+        "aqua" | "cyan" => (0, 255, 255, 255),
+        "orange" => (255, 165, 0, 255),
+        "brown" => (165, 42, 42, 255),
+        "pink" => (255, 192, 203, 255),
+        "gold" => (255, 215, 0, 255),
+        "indigo" => (75, 0, 130, 255),
+        "violet" => (238, 130, 238, 255),
+        "darkgray" | "darkgrey" => (169, 169, 169, 255),
+        "lightgray" | "lightgrey" => (211, 211, 211, 255),
+        _ => return None
+    };
+    Some(c)
+}
+
+/// parse an SVG transform list
+fn transform(s: &str) -> Transform {
+    let mut result = Transform::from_scale(Vector::splat(1.0));
+    let mut rest = s;
+    while let Some(open) = rest.find('(') {
+        let name = rest[.. open].trim().trim_start_matches(',').trim();
+        let close = match rest.find(')') {
+            Some(close) if close > open => close,
+            _ => break
+        };
+        let args = parse_numbers(&rest[open + 1 .. close]);
+        let tr = match (name, &args[..]) {
+            ("matrix", &[a, b, c, d, e, f]) => Transform::row_major(a, c, b, d, e, f),
+            ("translate", &[x]) => Transform::from_translation(Vector::new(x, 0.0)),
+            ("translate", &[x, y]) => Transform::from_translation(Vector::new(x, y)),
+            ("scale", &[s]) => Transform::from_scale(Vector::splat(s)),
+            ("scale", &[x, y]) => Transform::from_scale(Vector::new(x, y)),
+            ("rotate", &[a]) => Transform::from_rotation(a.to_radians()),
+            ("rotate", &[a, x, y]) => Transform::from_translation(Vector::new(x, y))
+                * Transform::from_rotation(a.to_radians())
+                * Transform::from_translation(Vector::new(-x, -y)),
+            ("skewX", &[a]) => Transform::row_major(1.0, a.to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+            ("skewY", &[a]) => Transform::row_major(1.0, 0.0, a.to_radians().tan(), 1.0, 0.0, 0.0),
+            _ => {
+                warn!("invalid transform {}({:?})", name, args);
+                Transform::from_scale(Vector::splat(1.0))
+            }
+        };
+        result *= tr;
+        rest = &rest[close + 1 ..];
+    }
+    result
+}
+
+// the factor by which lengths are scaled on average
+fn scale_factor(tr: &Transform) -> f32 {
+    (tr.m11() * tr.m22() - tr.m12() * tr.m21()).abs().sqrt()
+}
+
+fn blend_mode(s: &str) -> BlendMode {
+    match s {
+        "multiply" => BlendMode::Multiply,
+        "screen" => BlendMode::Screen,
+        "overlay" => BlendMode::Overlay,
+        "darken" => BlendMode::Darken,
+        "lighten" => BlendMode::Lighten,
+        "color-dodge" => BlendMode::ColorDodge,
+        "color-burn" => BlendMode::ColorBurn,
+        "hard-light" => BlendMode::HardLight,
+        "soft-light" => BlendMode::SoftLight,
+        "difference" => BlendMode::Difference,
+        "exclusion" => BlendMode::Exclusion,
+        "hue" => BlendMode::Hue,
+        "saturation" => BlendMode::Saturation,
+        "color" => BlendMode::Color,
+        "luminosity" => BlendMode::Luminosity,
+        _ => BlendMode::Normal
+    }
+}
+
+// build the outline of a basic shape or path in user space. None if it is not a shape.
+fn shape<O: Outline>(element: &Element, viewport: Vector) -> Option<O> {
+    let (w, h) = (viewport.x(), viewport.y());
+    let diagonal = (w * w + h * h).sqrt() / 2f32.sqrt();
+    // documents can contain anything, like overflowing numbers
+    let mut builder = PathBuilder::with_policy(ErrorPolicy::Ignore);
+    match element.local_name() {
+        "path" => {
+            if let Err(e) = parse_path_data(element.attr("d").unwrap_or(""), &mut builder) {
+                // render everything up to the error, like browsers do
+                warn!("invalid path data: {}", e);
+            }
+        }
+        "rect" => {
+            let origin = Vector::new(attr_length(element, "x", w), attr_length(element, "y", h));
+            let size = Vector::new(attr_length(element, "width", w), attr_length(element, "height", h));
+            if size.x() <= 0.0 || size.y() <= 0.0 {
+                return None;
+            }
+            let rx = element.attr("rx").and_then(|v| length(v, w));
+            let ry = element.attr("ry").and_then(|v| length(v, h));
+            let (rx, ry) = match (rx, ry) {
+                (Some(rx), Some(ry)) => (rx, ry),
+                (Some(r), None) | (None, Some(r)) => (r, r),
+                (None, None) => (0.0, 0.0)
+            };
+            // a zero radius in either direction disables rounding
+            let r = Vector::new(rx.max(0.0).min(size.x() / 2.0), ry.max(0.0).min(size.y() / 2.0));
+            let r = if r.x() == 0.0 || r.y() == 0.0 { Vector::default() } else { r };
+            builder.rounded_rect(Rect::new(origin, size), [r; 4]);
+        }
+        "circle" => {
+            let r = attr_length(element, "r", diagonal);
+            if r <= 0.0 {
+                return None;
+            }
+            builder.circle(Vector::new(attr_length(element, "cx", w), attr_length(element, "cy", h)), r);
+        }
+        "ellipse" => {
+            let r = Vector::new(attr_length(element, "rx", w), attr_length(element, "ry", h));
+            if r.x() <= 0.0 || r.y() <= 0.0 {
+                return None;
+            }
+            builder.ellipse(Vector::new(attr_length(element, "cx", w), attr_length(element, "cy", h)), r, 0.0);
+        }
+        "line" => {
+            builder.move_to(Vector::new(attr_length(element, "x1", w), attr_length(element, "y1", h)));
+            builder.line_to(Vector::new(attr_length(element, "x2", w), attr_length(element, "y2", h)));
+        }
+        name @ "polyline" | name @ "polygon" => {
+            let points = parse_numbers(element.attr("points").unwrap_or(""));
+            let mut points = points.chunks_exact(2).map(|c| Vector::new(c[0], c[1]));
+            builder.move_to(points.next()?);
+            for p in points {
+                builder.line_to(p);
+            }
+            if name == "polygon" {
+                builder.close();
+            }
+        }
+        _ => return None
+    }
+    Some(builder.into_outline())
+}
+
+struct Context<'a, S: Surface> {
+    doc: &'a SvgDocument,
+    surface: &'a mut S,
+    // how many more elements may be drawn
+    budget: Cell<usize>,
+}
+impl<'a, S: Surface> Context<'a, S> {
+    // take one element from the budget. false if it is used up.
+    fn spend(&self) -> bool {
+        match self.budget.get() {
+            0 => false,
+            1 => {
+                warn!("more than {} elements, skipping the rest", MAX_ELEMENTS);
+                self.budget.set(0);
+                true
+            }
+            n => {
+                self.budget.set(n - 1);
+                true
+            }
+        }
+    }
+    // whether `element` is drawn at all
+    fn enter(&self, element: &Element, depth: usize) -> bool {
+        if depth > MAX_DEPTH {
+            warn!("elements nested deeper than {} levels are skipped", MAX_DEPTH);
+            return false;
+        }
+        property(element, "display") != Some("none") && self.spend()
+    }
+    // the offset of a `use` element
+    fn use_translation(&self, element: &Element) -> Transform {
+        let x = attr_length(element, "x", self.doc.size.x());
+        let y = attr_length(element, "y", self.doc.size.y());
+        Transform::from_translation(Vector::new(x, y))
+    }
+
+    fn draw_children(&mut self, element: &Element, state: &State, depth: usize) {
+        for child in &element.children {
+            self.draw_element(child, state, depth);
+        }
+    }
+
+    // depth 0 is the root element
+    fn draw_element(&mut self, element: &Element, parent: &State, depth: usize) {
+        match element.local_name() {
+            "g" | "a" | "switch" | "svg" | "use" | "path" | "rect" | "circle" | "ellipse" | "line" | "polyline" | "polygon" => {}
+            // not rendered directly, or not supported
+            _ => return
+        }
+        if !self.enter(element, depth) {
+            return;
+        }
+
+        let mut state = parent.child(element);
+        if element.local_name() == "use" {
+            state.transform *= self.use_translation(element);
+        }
+
+        let outline: Option<S::Outline> = shape(element, self.doc.size);
+
+        // opacity, blending and clipping of the element as a whole need a layer
+        let opacity = property(element, "opacity").and_then(number).map(clamp).unwrap_or(1.0);
+        let blend_mode = property(element, "mix-blend-mode").map(blend_mode).unwrap_or(BlendMode::Normal);
+        let clip = property(element, "clip-path").and_then(url).and_then(|id| {
+            let bounds = match outline {
+                Some(ref outline) => outline.bounding_box(),
+                None => self.content_bounds(element, depth)
+            };
+            self.clip_path(id, &state, bounds, depth)
+        });
+        let layer = if opacity < 1.0 || blend_mode != BlendMode::Normal || clip.is_some() {
+            Some(self.surface.push_layer(opacity, blend_mode, clip.as_ref()))
+        } else {
+            None
+        };
+
+        match element.local_name() {
+            "use" => if let Some(target) = href(element).and_then(|id| self.doc.find(id)) {
+                match target.local_name() {
+                    // an instance of a symbol or svg is a new viewport, with the size of the use element if it has one
+                    "symbol" | "svg" => if self.enter(target, depth + 1) {
+                        let (w, h) = (self.doc.size.x(), self.doc.size.y());
+                        let size = |name: &str, reference: f32| element.attr(name)
+                            .or_else(|| target.attr(name))
+                            .and_then(|v| length(v, reference))
+                            .unwrap_or(reference);
+                        let origin = Vector::new(attr_length(target, "x", w), attr_length(target, "y", h));
+                        let target_state = state.child(target);
+                        self.draw_viewport(target, &target_state, origin, Vector::new(size("width", w), size("height", h)), depth + 1);
+                    },
+                    _ => self.draw_element(target, &state, depth + 1)
+                }
+            },
+            "svg" if depth == 0 => {
+                // the document has already been sized, and the surface clips to it
+                let inner = State { transform: state.transform * view_box_transform(element, self.doc.size), .. state };
+                self.draw_children(element, &inner, depth + 1);
+            }
+            "svg" => {
+                let (w, h) = (self.doc.size.x(), self.doc.size.y());
+                let origin = Vector::new(attr_length(element, "x", w), attr_length(element, "y", h));
+                let size = Vector::new(
+                    element.attr("width").and_then(|v| length(v, w)).unwrap_or(w),
+                    element.attr("height").and_then(|v| length(v, h)).unwrap_or(h)
+                );
+                self.draw_viewport(element, &state, origin, size, depth);
+            }
+            "g" | "a" | "switch" => self.draw_children(element, &state, depth + 1),
+            _ => if let Some(outline) = outline {
+                if state.visible {
+                    let bounds = outline.bounding_box();
+                    self.draw_shape(outline, bounds, &state);
+                }
+            }
+        }
+
+        if let Some(layer) = layer {
+            self.surface.pop_layer(layer);
+        }
+    }
+
+    // draw the children of a nested `svg` or an instance of a `symbol` into the viewport at `origin` with `size`
+    fn draw_viewport(&mut self, element: &Element, state: &State, origin: Vector, size: Vector, depth: usize) {
+        if !(size.x() > 0.0 && size.y() > 0.0) {
+            return;
+        }
+        let tr = state.transform * Transform::from_translation(origin);
+
+        // content outside of the viewport is hidden unless overflow is visible
+        let clip = match property(element, "overflow") {
+            Some("visible") | Some("auto") => None,
+            _ => {
+                let mut builder = PathBuilder::with_policy(ErrorPolicy::Ignore);
+                builder.rect(Rect::new(Vector::default(), size));
+                let outline: S::Outline = builder.into_outline();
+                Some(self.surface.clip_path(outline.transform(tr), FillRule::NonZero))
+            }
+        };
+        let layer = clip.as_ref().map(|clip| self.surface.push_layer(1.0, BlendMode::Normal, Some(clip)));
+
+        let inner = State { transform: tr * view_box_transform(element, size), .. state.clone() };
+        self.draw_children(element, &inner, depth + 1);
+
+        if let Some(layer) = layer {
+            self.surface.pop_layer(layer);
+        }
+    }
+
+    // the bounding box of the children of a group in its user space
+    fn content_bounds(&self, element: &Element, depth: usize) -> Option<Rect> {
+        element.children.iter()
+            .filter_map(|child| self.bounds(child, Transform::from_scale(Vector::splat(1.0)), depth + 1))
+            .fold(None, |a: Option<Rect>, b| Some(a.map_or(b, |a| a.union_rect(b))))
+    }
+    // the bounding box of `element`, transformed by `tr`
+    fn bounds(&self, element: &Element, tr: Transform, depth: usize) -> Option<Rect> {
+        if !self.enter(element, depth) {
+            return None;
+        }
+        let tr = match element.attr("transform") {
+            Some(t) => tr * transform(t),
+            None => tr
+        };
+        match element.local_name() {
+            "g" | "a" | "switch" => element.children.iter()
+                .filter_map(|child| self.bounds(child, tr, depth + 1))
+                .fold(None, |a: Option<Rect>, b| Some(a.map_or(b, |a| a.union_rect(b)))),
+            "use" => {
+                let target = href(element).and_then(|id| self.doc.find(id))?;
+                match target.local_name() {
+                    // viewports are not measured
+                    "symbol" | "svg" => None,
+                    _ => self.bounds(target, tr * self.use_translation(element), depth + 1)
+                }
+            }
+            _ => shape::<Path>(element, self.doc.size)?.transform(tr).bounding_box()
+        }
+    }
+
+    fn draw_shape(&mut self, outline: S::Outline, bounds: Option<Rect>, state: &State) {
+        let fill = self.paint(&state.fill, state.fill_opacity, state, bounds);
+        let stroke = self.paint(&state.stroke, state.stroke_opacity, state, bounds).map(|paint| {
+            let scale = scale_factor(&state.transform);
+            let join = match state.line_join {
+                LineJoin::Miter(_) => LineJoin::Miter(state.miter_limit),
+                join => join
+            };
+            let dashes: Vec<f32> = state.dash_array.iter().map(|&d| d * scale).collect();
+            let line = LineStyle {
+                width: state.stroke_width * scale,
+                cap: state.line_cap,
+                join,
+                ..LineStyle::default(0.0)
+            }.dashed(&dashes, state.dash_offset * scale);
+            (paint, line)
+        });
+        if fill.is_none() && stroke.is_none() {
+            return;
+        }
+        let style = self.surface.build_style(PathStyle { fill, stroke, fill_rule: state.fill_rule });
+        self.surface.draw_path(outline.transform(state.transform), &style, None);
+    }
+
+    // `bounds` is the bounding box of the element in user space, for objectBoundingBox units
+    fn paint(&self, spec: &PaintSpec, opacity: f32, state: &State, bounds: Option<Rect>) -> Option<Paint<S>> {
+        let alpha = |(r, g, b, a): Rgba8| (r, g, b, (a as f32 * opacity).round() as u8);
+        match *spec {
+            PaintSpec::None => None,
+            PaintSpec::Color(c) => Some(Paint::Solid(alpha(c))),
+            PaintSpec::CurrentColor => Some(Paint::Solid(alpha(state.color))),
+            PaintSpec::Url(ref id) => {
+                let element = self.doc.find(id)?;
+                let stops: Vec<GradientStop> = self.stops(element, 0).into_iter()
+                    .map(|stop| GradientStop { color: alpha(stop.color), .. stop })
+                    .collect();
+                if stops.is_empty() {
+                    return None;
+                }
+                let spread = match element.attr("spreadMethod") {
+                    Some("reflect") => Spread::Reflect,
+                    Some("repeat") => Spread::Repeat,
+                    _ => Spread::Pad
+                };
+
+                // maps gradient coordinates into device space
+                let user_space = element.attr("gradientUnits") == Some("userSpaceOnUse");
+                let units = match (user_space, bounds) {
+                    (true, _) => Transform::from_scale(Vector::splat(1.0)),
+                    (false, Some(b)) => Transform::from_translation(b.origin()) * Transform::from_scale(b.size()),
+                    (false, None) => return None
+                };
+                let gradient_transform = element.attr("gradientTransform").map(transform)
+                    .unwrap_or(Transform::from_scale(Vector::splat(1.0)));
+                let tr = state.transform * units * gradient_transform;
+
+                let (w, h) = if user_space { (self.doc.size.x(), self.doc.size.y()) } else { (1.0, 1.0) };
+                let get = |name: &str, reference: f32, default: f32| {
+                    element.attr(name).and_then(|v| length(v, reference)).unwrap_or(default)
+                };
+                match element.local_name() {
+                    "linearGradient" => {
+                        let start = Vector::new(get("x1", w, 0.0), get("y1", h, 0.0));
+                        let end = Vector::new(get("x2", w, w), get("y2", h, 0.0));
+                        Some(Paint::LinearGradient(LinearGradient { start: tr * start, end: tr * end, stops, spread }))
+                    }
+                    "radialGradient" => {
+                        let center = Vector::new(get("cx", w, 0.5 * w), get("cy", h, 0.5 * h));
+                        let radius = get("r", (w * w + h * h).sqrt() / 2f32.sqrt(), 0.5 * w);
+                        let focus = Vector::new(get("fx", w, center.x()), get("fy", h, center.y()));
+                        Some(Paint::RadialGradient(RadialGradient {
+                            center: tr * center,
+                            radius: radius * scale_factor(&tr),
+                            focus: tr * focus,
+                            stops,
+                            spread
+                        }))
+                    }
+                    _ => None
+                }
+            }
+        }
+    }
+
+    // the stops of a gradient, following href if it has none itself
+    fn stops(&self, gradient: &Element, depth: usize) -> Vec<GradientStop> {
+        let stops: Vec<GradientStop> = gradient.children.iter()
+            .filter(|e| e.local_name() == "stop")
+            .scan(0.0, |last, stop| {
+                let offset = stop.attr("offset").and_then(|o| length(o, 1.0)).map(clamp).unwrap_or(0.0);
+                // offsets can't decrease
+                *last = offset.max(*last);
+                let color = property(stop, "stop-color").and_then(color).unwrap_or((0, 0, 0, 255));
+                let opacity = property(stop, "stop-opacity").and_then(number).map(clamp).unwrap_or(1.0);
+                let color = (color.0, color.1, color.2, (color.3 as f32 * opacity).round() as u8);
+                Some(GradientStop { offset: *last, color })
+            })
+            .collect();
+        if !stops.is_empty() || depth > MAX_DEPTH {
+            return stops;
+        }
+        match href(gradient).and_then(|id| self.doc.find(id)) {
+            Some(parent) => self.stops(parent, depth + 1),
+            None => stops
+        }
+    }
+
+    fn clip_path(&mut self, id: &str, state: &State, bounds: Option<Rect>, depth: usize) -> Option<S::ClipPath> {
+        let element = self.doc.find(id)?;
+        if element.local_name() != "clipPath" {
+            return None;
+        }
+        let mut tr = state.transform;
+        if let Some(t) = element.attr("transform") {
+            tr *= transform(t);
+        }
+        if element.attr("clipPathUnits") == Some("objectBoundingBox") {
+            // without a bounding box there is nothing to clip to, so nothing is shown
+            let b = bounds.unwrap_or_else(|| Rect::new(Vector::default(), Vector::default()));
+            tr = tr * Transform::from_translation(b.origin()) * Transform::from_scale(b.size());
+        }
+        let clip_state = State { transform: tr, .. state.clone() };
+
+        // the clip is the union of the children, each filled with its own clip rule
+        let mut shapes = vec![];
+        self.clip_children(element, &clip_state, &mut shapes, depth);
+        let (outline, clip_rule) = match shapes.len() {
+            0 => (S::Outline::empty(), FillRule::NonZero),
+            1 => shapes.pop().unwrap(),
+            _ => {
+                let union = shapes.iter().fold(S::Outline::empty(), |union, (shape, rule)| {
+                    boolean(BooleanOp::Union, &union, FillRule::NonZero, shape, *rule, CLIP_TOLERANCE)
+                });
+                (union, FillRule::NonZero)
+            }
+        };
+        Some(self.surface.clip_path(outline, clip_rule))
+    }
+    fn clip_children(&self, element: &Element, state: &State, shapes: &mut Vec<(S::Outline, FillRule)>, depth: usize) {
+        for child in &element.children {
+            self.clip_element(child, state, shapes, depth);
+        }
+    }
+    fn clip_element(&self, element: &Element, parent: &State, shapes: &mut Vec<(S::Outline, FillRule)>, depth: usize) {
+        if !self.enter(element, depth) {
+            return;
+        }
+        let mut state = parent.child(element);
+        if element.local_name() == "use" {
+            state.transform *= self.use_translation(element);
+            if let Some(target) = href(element).and_then(|id| self.doc.find(id)) {
+                self.clip_element(target, &state, shapes, depth + 1);
+            }
+        } else if let Some(shape) = shape::<S::Outline>(element, self.doc.size) {
+            shapes.push((shape.transform(state.transform), state.clip_rule));
+        }
+    }
+}
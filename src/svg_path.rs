@@ -0,0 +1,226 @@
+//! Parser for SVG path data (the `d` attribute).
+
+use crate::{Outline, PathBuilder, PathError, Vector};
+use std::fmt;
+use std::error::Error;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathDataErrorKind {
+    /// a character that is neither a command nor part of a number
+    UnexpectedCharacter(char),
+    /// the command needs more arguments
+    ExpectedNumber,
+    /// arc flags have to be `0` or `1`
+    ExpectedFlag,
+    /// path data has to start with a move command
+    ExpectedMoveTo,
+    /// a number too large to be represented
+    OutOfRange,
+}
+
+/// The reason and byte offset at which parsing failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathDataError {
+    pub position: usize,
+    pub kind: PathDataErrorKind,
+}
+impl fmt::Display for PathDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            PathDataErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character {:?}", c)?,
+            PathDataErrorKind::ExpectedNumber => write!(f, "expected a number")?,
+            PathDataErrorKind::ExpectedFlag => write!(f, "expected a flag (0 or 1)")?,
+            PathDataErrorKind::ExpectedMoveTo => write!(f, "path data has to start with a move command")?,
+            PathDataErrorKind::OutOfRange => write!(f, "number out of range")?,
+        }
+        write!(f, " at position {}", self.position)
+    }
+}
+impl Error for PathDataError {}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+impl<'a> Parser<'a> {
+    fn error(&self, kind: PathDataErrorKind) -> PathDataError {
+        PathDataError { position: self.pos, kind }
+    }
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).cloned()
+    }
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') | Some(b'\x0C') = self.peek() {
+            self.pos += 1;
+        }
+    }
+    // whitespace with at most one comma
+    fn skip_separator(&mut self) {
+        self.skip_whitespace();
+        if self.peek() == Some(b',') {
+            self.pos += 1;
+            self.skip_whitespace();
+        }
+    }
+    fn at_number(&self) -> bool {
+        matches!(self.peek(), Some(b'0' ..= b'9') | Some(b'.') | Some(b'-') | Some(b'+'))
+    }
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(b'0' ..= b'9') = self.peek() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+    fn number(&mut self) -> Result<f32, PathDataError> {
+        let start = self.pos;
+        if let Some(b'-') | Some(b'+') = self.peek() {
+            self.pos += 1;
+        }
+        let mut n = self.digits();
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            n += self.digits();
+        }
+        if n == 0 {
+            self.pos = start;
+            return Err(self.error(PathDataErrorKind::ExpectedNumber));
+        }
+        // only an exponent if digits follow, so "1e" stays an error at 'e'
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if let Some(b'-') | Some(b'+') = self.peek() {
+                self.pos += 1;
+            }
+            if self.digits() == 0 {
+                self.pos = mantissa_end;
+            }
+        }
+        let s = std::str::from_utf8(&self.data[start .. self.pos]).unwrap();
+        let value: f32 = s.parse().map_err(|_| PathDataError {
+            position: start,
+            kind: PathDataErrorKind::ExpectedNumber
+        })?;
+        if !value.is_finite() {
+            return Err(PathDataError { position: start, kind: PathDataErrorKind::OutOfRange });
+        }
+        self.skip_separator();
+        Ok(value)
+    }
+    fn vector(&mut self) -> Result<Vector, PathDataError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok(Vector::new(x, y))
+    }
+    fn flag(&mut self) -> Result<bool, PathDataError> {
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error(PathDataErrorKind::ExpectedFlag))
+        };
+        self.pos += 1;
+        self.skip_separator();
+        Ok(flag)
+    }
+}
+
+/// Parse SVG path data and append it to `builder`.
+///
+/// Supports the full grammar of the SVG 1.1 path data:
+/// absolute and relative `M L H V C S Q T A Z` commands, implicitly repeated commands
+/// and compact numbers like `M0.5.5-1e2`.
+/// A leading `m` is absolute, even if `builder` already has a current point.
+/// On error, everything up to the failing command has already been added to the builder.
+/// Commands that would produce non-finite coordinates fail with `OutOfRange`,
+/// regardless of the error policy of `builder`.
+pub fn parse_path_data<O: Outline>(data: &str, builder: &mut PathBuilder<O>) -> Result<(), PathDataError> {
+    let mut p = Parser { data: data.as_bytes(), pos: 0 };
+
+    // the builder keeps track of the current point and the control point for smooth curves.
+    let mut command: Option<u8> = None;
+
+    p.skip_whitespace();
+    while let Some(c) = p.peek() {
+        let command_start = p.pos;
+        let cmd = match c {
+            b'M' | b'm' | b'L' | b'l' | b'H' | b'h' | b'V' | b'v' | b'C' | b'c' |
+            b'S' | b's' | b'Q' | b'q' | b'T' | b't' | b'A' | b'a' | b'Z' | b'z' => {
+                p.pos += 1;
+                p.skip_whitespace();
+                c
+            }
+            // implicit repetition. a repeated move is a line.
+            _ if p.at_number() => match command {
+                Some(b'M') => b'L',
+                Some(b'm') => b'l',
+                Some(b'Z') | Some(b'z') | None => return Err(p.error(PathDataErrorKind::UnexpectedCharacter(c as char))),
+                Some(cmd) => cmd
+            },
+            _ => return Err(p.error(PathDataErrorKind::UnexpectedCharacter(data[p.pos ..].chars().next().unwrap())))
+        };
+        let first = command.is_none();
+        if first && cmd != b'M' && cmd != b'm' {
+            return Err(PathDataError { position: command_start, kind: PathDataErrorKind::ExpectedMoveTo });
+        }
+        command = Some(cmd);
+        let start = p.pos;
+
+        let result = match cmd {
+            b'M' => builder.try_move_to(p.vector()?),
+            // a leading move is absolute, whatever the builder has drawn before
+            b'm' if first => builder.try_move_to(p.vector()?),
+            b'm' => builder.try_rel_move_to(p.vector()?),
+            b'L' => builder.try_line_to(p.vector()?),
+            b'l' => builder.try_rel_line_to(p.vector()?),
+            b'H' => builder.try_horizontal_line_to(p.number()?),
+            b'h' => builder.try_rel_horizontal_line_to(p.number()?),
+            b'V' => builder.try_vertical_line_to(p.number()?),
+            b'v' => builder.try_rel_vertical_line_to(p.number()?),
+            b'C' => builder.try_cubic_curve_to(p.vector()?, p.vector()?, p.vector()?),
+            b'c' => builder.try_rel_cubic_curve_to(p.vector()?, p.vector()?, p.vector()?),
+            b'S' => builder.try_smooth_cubic_curve_to(p.vector()?, p.vector()?),
+            b's' => builder.try_rel_smooth_cubic_curve_to(p.vector()?, p.vector()?),
+            b'Q' => builder.try_quadratic_curve_to(p.vector()?, p.vector()?),
+            b'q' => builder.try_rel_quadratic_curve_to(p.vector()?, p.vector()?),
+            b'T' => builder.try_smooth_quadratic_curve_to(p.vector()?),
+            b't' => builder.try_rel_smooth_quadratic_curve_to(p.vector()?),
+            b'A' | b'a' => {
+                let radii = p.vector()?;
+                let x_rotation = p.number()?.to_radians();
+                let large_arc = p.flag()?;
+                let sweep = p.flag()?;
+                let to = p.vector()?;
+                if cmd == b'A' {
+                    builder.try_arc_to(radii, x_rotation, large_arc, sweep, to)
+                } else {
+                    builder.try_rel_arc_to(radii, x_rotation, large_arc, sweep, to)
+                }
+            }
+            // drawing continues with a new sub path at the start point
+            b'Z' | b'z' => {
+                builder.close();
+                Ok(())
+            }
+            _ => unreachable!()
+        };
+        // numbers that are finite on their own can still overflow once they are made absolute
+        result.map_err(|e| match e {
+            PathError::NonFinite(_) => PathDataError { position: start, kind: PathDataErrorKind::OutOfRange },
+            PathError::NoStartPoint => PathDataError { position: start, kind: PathDataErrorKind::ExpectedMoveTo },
+        })?;
+    }
+    Ok(())
+}
+
+/// Parse a list of numbers separated by whitespace and/or commas, stopping at the first error.
+pub(crate) fn parse_numbers(data: &str) -> Vec<f32> {
+    let mut p = Parser { data: data.as_bytes(), pos: 0 };
+    let mut numbers = vec![];
+    p.skip_whitespace();
+    while let Ok(n) = p.number() {
+        numbers.push(n);
+    }
+    numbers
+}
@@ -0,0 +1,232 @@
+//! Just enough of an XML parser to read SVG documents.
+//!
+//! Builds a tree of elements and their attributes. Text, comments, processing instructions,
+//! CDATA sections and the doctype are skipped. Only the predefined and numeric entities are decoded.
+
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+}
+impl Element {
+    /// the name without namespace prefix
+    pub fn local_name(&self) -> &str {
+        local_name(&self.name)
+    }
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+pub fn local_name(name: &str) -> &str {
+    match name.rfind(':') {
+        Some(i) => &name[i + 1 ..],
+        None => name
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct XmlError {
+    pub position: usize,
+    pub message: &'static str,
+}
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+struct Parser<'a> {
+    data: &'a str,
+    pos: usize,
+}
+impl<'a> Parser<'a> {
+    fn error(&self, message: &'static str) -> XmlError {
+        XmlError { position: self.pos, message }
+    }
+    fn rest(&self) -> &'a str {
+        &self.data[self.pos ..]
+    }
+    fn starts_with(&self, s: &str) -> bool {
+        self.rest().starts_with(s)
+    }
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    // skip past the next occurrence of `end`
+    fn skip_past(&mut self, end: &str) -> Result<(), XmlError> {
+        match self.rest().find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                Ok(())
+            }
+            None => Err(self.error("unterminated markup"))
+        }
+    }
+    fn name(&mut self) -> Result<&'a str, XmlError> {
+        let rest = self.rest();
+        let len = rest.find(|c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=').unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += len;
+        Ok(&rest[.. len])
+    }
+    // skips comments, processing instructions, CDATA, doctype and text
+    fn skip_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            if self.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.starts_with("<![CDATA[") {
+                self.skip_past("]]>")?;
+            } else if self.starts_with("<!") {
+                // doctype, possibly with an internal subset in brackets
+                let mut depth = 0;
+                let start = self.pos;
+                let end = self.rest().char_indices().find(|&(_, c)| {
+                    match c {
+                        '[' => depth += 1,
+                        ']' => depth -= 1,
+                        '>' if depth == 0 => return true,
+                        _ => {}
+                    }
+                    false
+                });
+                match end {
+                    Some((i, _)) => self.pos = start + i + 1,
+                    None => return Err(self.error("unterminated doctype"))
+                }
+            } else if self.starts_with("<") || self.pos == self.data.len() {
+                return Ok(());
+            } else {
+                // text
+                self.pos += self.rest().find('<').unwrap_or(self.rest().len());
+            }
+        }
+    }
+    fn attribute_value(&mut self) -> Result<String, XmlError> {
+        let quote = match self.rest().chars().next() {
+            Some(q @ '"') | Some(q @ '\'') => q,
+            _ => return Err(self.error("expected a quoted attribute value"))
+        };
+        self.pos += 1;
+        let len = match self.rest().find(quote) {
+            Some(len) => len,
+            None => return Err(self.error("unterminated attribute value"))
+        };
+        let value = decode_entities(&self.rest()[.. len]);
+        self.pos += len + 1;
+        Ok(value)
+    }
+    // parses the start tag. returns the element and whether it is empty (`/>`).
+    fn start_tag(&mut self) -> Result<(Element, bool), XmlError> {
+        self.pos += 1; // '<'
+        let name = self.name()?.to_owned();
+        let mut attributes = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("/>") {
+                self.pos += 2;
+                return Ok((Element { name, attributes, children: vec![] }, true));
+            }
+            if self.starts_with(">") {
+                self.pos += 1;
+                return Ok((Element { name, attributes, children: vec![] }, false));
+            }
+            if self.pos == self.data.len() {
+                return Err(self.error("unterminated start tag"));
+            }
+            let attr_name = self.name()?.to_owned();
+            self.skip_whitespace();
+            if !self.starts_with("=") {
+                return Err(self.error("expected '='"));
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+            let value = self.attribute_value()?;
+            attributes.push((attr_name, value));
+        }
+    }
+}
+
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_owned();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[.. i]);
+        rest = &rest[i ..];
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1 .. end] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                e if e.starts_with("#x") => u32::from_str_radix(&e[2 ..], 16).ok().and_then(std::char::from_u32),
+                e if e.starts_with('#') => e[1 ..].parse().ok().and_then(std::char::from_u32),
+                _ => None
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1 ..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1 ..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// parse the document and return the root element
+pub fn parse(data: &str) -> Result<Element, XmlError> {
+    let mut p = Parser { data, pos: 0 };
+    let mut stack: Vec<Element> = vec![];
+    loop {
+        p.skip_misc()?;
+        if p.pos == data.len() {
+            return Err(p.error(if stack.is_empty() { "no root element" } else { "unexpected end of document" }));
+        }
+        if p.starts_with("</") {
+            p.pos += 2;
+            let name = p.name()?;
+            p.skip_whitespace();
+            if !p.starts_with(">") {
+                return Err(p.error("expected '>'"));
+            }
+            p.pos += 1;
+            let element = match stack.pop() {
+                Some(e) if e.name == name => e,
+                _ => return Err(p.error("mismatched end tag"))
+            };
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(element),
+                None => return Ok(element)
+            }
+        } else {
+            let (element, empty) = p.start_tag()?;
+            if !empty {
+                stack.push(element);
+            } else {
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use vector::*;
+
+fn rect(x: f32, y: f32, w: f32, h: f32) -> Path {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.rect(Rect::new(Vector::new(x, y), Vector::new(w, h)));
+    builder.into_outline()
+}
+
+// the signed area of all contours, which is the covered area for outlines without overlaps
+fn area(path: &Path) -> f32 {
+    let mut area = 0.0;
+    let mut contour: Vec<Vector> = vec![];
+    let mut finish = |contour: &mut Vec<Vector>| {
+        let n = contour.len();
+        for i in 0 .. n {
+            let (a, b) = (contour[i], contour[(i + 1) % n]);
+            area += 0.5 * (a.x() * b.y() - b.x() * a.y());
+        }
+        contour.clear();
+    };
+    for event in path.events() {
+        match event {
+            PathEvent::MoveTo(p) => {
+                finish(&mut contour);
+                contour.push(p);
+            }
+            PathEvent::LineTo(p) => contour.push(p),
+            PathEvent::Close => {}
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+    finish(&mut contour);
+    area.abs()
+}
+
+fn op(op: BooleanOp, a: &Path, b: &Path) -> Path {
+    boolean::boolean(op, a, FillRule::NonZero, b, FillRule::NonZero, 0.01)
+}
+
+#[test]
+fn overlapping_rects() {
+    // 100 + 100 with an overlap of 25
+    let a = rect(0.0, 0.0, 10.0, 10.0);
+    let b = rect(5.0, 5.0, 10.0, 10.0);
+    let cases = [
+        (BooleanOp::Union, 175.0),
+        (BooleanOp::Intersection, 25.0),
+        (BooleanOp::Difference, 75.0),
+        (BooleanOp::Xor, 150.0),
+    ];
+    for &(o, expected) in &cases {
+        let result = op(o, &a, &b);
+        assert!((area(&result) - expected).abs() < 1e-3, "{:?}: {}", o, area(&result));
+        for &fill_rule in &[FillRule::NonZero, FillRule::EvenOdd] {
+            let inside = |x, y| result.contains(Vector::new(x, y), fill_rule, 0.01);
+            assert_eq!(inside(2.0, 2.0), o != BooleanOp::Intersection);
+            assert_eq!(inside(7.0, 7.0), o == BooleanOp::Union || o == BooleanOp::Intersection);
+            assert_eq!(inside(12.0, 12.0), o == BooleanOp::Union || o == BooleanOp::Xor);
+            assert!(!inside(2.0, 12.0));
+        }
+    }
+}
+
+#[test]
+fn disjoint_and_nested() {
+    let outer = rect(0.0, 0.0, 10.0, 10.0);
+    let inner = rect(2.0, 2.0, 2.0, 2.0);
+    let far = rect(20.0, 0.0, 1.0, 1.0);
+    assert!((area(&op(BooleanOp::Union, &outer, &far)) - 101.0).abs() < 1e-3);
+    assert!(area(&op(BooleanOp::Intersection, &outer, &far)) < 1e-3);
+    let hole = op(BooleanOp::Difference, &outer, &inner);
+    // the hole winds the other way, so the signed areas subtract
+    assert!((area(&hole) - 96.0).abs() < 1e-3, "{}", area(&hole));
+    assert!(!hole.contains(Vector::new(3.0, 3.0), FillRule::NonZero, 0.01));
+    assert!(hole.contains(Vector::new(1.0, 1.0), FillRule::NonZero, 0.01));
+}
+
+#[test]
+fn shared_edges() {
+    let a = rect(0.0, 0.0, 10.0, 10.0);
+    let b = rect(10.0, 0.0, 10.0, 10.0);
+    let union = op(BooleanOp::Union, &a, &b);
+    assert!((area(&union) - 200.0).abs() < 1e-3);
+    // the shared edge is gone
+    assert_eq!(union.events().filter(|e| matches!(e, PathEvent::MoveTo(_))).count(), 1);
+    assert!(area(&op(BooleanOp::Intersection, &a, &b)) < 1e-3);
+}
+
+#[test]
+fn circles() {
+    let circle = |x: f32| {
+        let mut builder = PathBuilder::<Path>::new();
+        builder.circle(Vector::new(x, 0.0), 10.0);
+        builder.into_outline()
+    };
+    let (a, b) = (circle(0.0), circle(10.0));
+    let r2 = 100.0f32;
+    // two circles of radius r at distance r overlap by (2π/3 - √3/2) r²
+    let lens = (2.0 * std::f32::consts::PI / 3.0 - 3f32.sqrt() / 2.0) * r2;
+    let disc = std::f32::consts::PI * r2;
+    let cases = [
+        (BooleanOp::Union, 2.0 * disc - lens),
+        (BooleanOp::Intersection, lens),
+        (BooleanOp::Difference, disc - lens),
+        (BooleanOp::Xor, 2.0 * (disc - lens)),
+    ];
+    for &(o, expected) in &cases {
+        let result = area(&op(o, &a, &b));
+        assert!((result - expected).abs() < 0.5, "{:?}: {} vs {}", o, result, expected);
+    }
+}
+
+#[test]
+fn non_finite_contours_are_ignored() {
+    let a = rect(0.0, 0.0, 10.0, 10.0);
+    let mut builder = PathBuilder::<Path>::with_policy(ErrorPolicy::Ignore);
+    builder.move_to(Vector::new(0.0, 0.0));
+    builder.line_to(Vector::new(f32::NAN, 5.0));
+    builder.line_to(Vector::new(5.0, f32::INFINITY));
+    let b: Path = builder.into_outline();
+    let union = op(BooleanOp::Union, &a, &b);
+    assert!((area(&union) - 100.0).abs() < 1e-3);
+}
+
+#[test]
+fn uneven_edge_distribution() {
+    // many edges in a tiny area next to a huge rect, whose coordinates are far beyond i32 in units of the tolerance
+    let mut builder = PathBuilder::<Path>::new();
+    builder.circle(Vector::new(0.0, 0.0), 1.0);
+    let circle = builder.into_outline();
+    let big = rect(1e30, 0.0, 1e30, 1e30);
+    let union = boolean::boolean::<_, _, Path>(BooleanOp::Union, &circle, FillRule::NonZero, &big, FillRule::NonZero, 1e-6);
+    assert!(union.contains(Vector::new(0.0, 0.0), FillRule::NonZero, 1e-6));
+    assert!(union.contains(Vector::new(1.5e30, 0.5e30), FillRule::NonZero, 1e-6));
+    assert!(!union.contains(Vector::new(1.5, 0.0), FillRule::NonZero, 1e-6));
+}
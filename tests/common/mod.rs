@@ -0,0 +1,149 @@
+//! A surface that keeps everything drawn onto it, for inspection in tests.
+
+#![allow(dead_code)]
+
+use vector::*;
+
+pub struct Draw {
+    pub path: Path,
+    pub style: PathStyle<Log>,
+    pub clip: Option<usize>,
+    // the number of open layers
+    pub depth: usize,
+}
+
+pub struct Texture {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    pub stride: usize,
+    pub format: PixelFormat,
+}
+
+#[derive(Default)]
+pub struct Log {
+    pub size: Vector,
+    pub draws: Vec<Draw>,
+    pub clips: Vec<(Path, FillRule)>,
+    pub textures: Vec<Texture>,
+    // opacity, blend mode and clip of every layer pushed
+    pub layers: Vec<(f32, BlendMode, Option<usize>)>,
+    pub depth: usize,
+}
+impl Surface for Log {
+    type Outline = Path;
+    type Style = PathStyle<Log>;
+    type ClipPath = usize;
+    type Image = usize;
+
+    fn new(size: Vector) -> Self {
+        Log { size, .. Log::default() }
+    }
+    fn build_style(&mut self, style: PathStyle<Self>) -> Self::Style {
+        style
+    }
+    fn draw_path(&mut self, path: Path, style: &Self::Style, clip: Option<&usize>) {
+        self.draws.push(Draw { path, style: style.clone(), clip: clip.cloned(), depth: self.depth });
+    }
+    fn clip_path(&mut self, path: Path, fill_rule: FillRule) -> usize {
+        self.clips.push((path, fill_rule));
+        self.clips.len() - 1
+    }
+    fn texture_with_stride(&mut self, width: u32, height: u32, data: &[u8], stride: usize, format: PixelFormat)
+        -> Result<usize, TextureError>
+    {
+        self.textures.push(Texture { width, height, data: data.to_vec(), stride, format });
+        Ok(self.textures.len() - 1)
+    }
+    fn push_layer(&mut self, opacity: f32, blend_mode: BlendMode, clip: Option<&usize>) -> Layer {
+        self.layers.push((opacity, blend_mode, clip.cloned()));
+        self.depth += 1;
+        Layer::empty()
+    }
+    fn pop_layer(&mut self, _layer: Layer) {
+        self.depth -= 1;
+    }
+}
+
+/// draw the svg document onto a new `Log`
+pub fn draw_svg(svg: &str) -> Log {
+    let doc = SvgDocument::parse(svg).unwrap();
+    let mut log = Log::new(doc.size());
+    doc.draw(&mut log);
+    assert_eq!(log.depth, 0, "unbalanced layers");
+    log
+}
+
+pub fn approx(a: f32, b: f32, eps: f32) -> bool {
+    (a - b).abs() <= eps
+}
+pub fn approx_rect(r: Rect, origin: (f32, f32), size: (f32, f32), eps: f32) -> bool {
+    approx(r.origin_x(), origin.0, eps) && approx(r.origin_y(), origin.1, eps)
+        && approx(r.width(), size.0, eps) && approx(r.height(), size.1, eps)
+}
+
+// the signed area of the polygon through the end points of each segment.
+// positive for contours running in the direction of increasing angles.
+fn endpoint_area(events: &[PathEvent]) -> f32 {
+    let points: Vec<Vector> = events.iter().filter_map(|e| match *e {
+        PathEvent::MoveTo(p) | PathEvent::LineTo(p) | PathEvent::QuadTo(_, p) | PathEvent::CubicTo(_, _, p) => Some(p),
+        PathEvent::Close => None,
+    }).collect();
+    let n = points.len();
+    (0 .. n).map(|i| {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        0.5 * (a.x() * b.y() - b.x() * a.y())
+    }).sum()
+}
+
+type Shape<O> = Box<dyn Fn(&mut PathBuilder<O>)>;
+
+/// Check that each closed shape of `PathBuilder` starts its own contour after a `line_to`,
+/// runs in the documented direction and leaves the current point at its start.
+pub fn check_shapes<O: Outline>() {
+    let rect = Rect::new(Vector::new(10.0, 20.0), Vector::new(30.0, 40.0));
+    let radii = [Vector::splat(5.0); 4];
+    // (shape, start point, positive area)
+    let shapes: Vec<(&str, Shape<O>, Vector, bool)> = vec![
+        ("circle", Box::new(|b| b.circle(Vector::new(50.0, 50.0), 10.0)), Vector::new(60.0, 50.0), false),
+        ("ellipse", Box::new(|b| b.ellipse(Vector::new(50.0, 50.0), Vector::new(20.0, 10.0), 0.5 * std::f32::consts::PI)),
+            Vector::new(50.0, 70.0), false),
+        ("rect", Box::new(move |b| b.rect(rect)), rect.origin(), true),
+        ("rounded_rect", Box::new(move |b| b.rounded_rect(rect, radii)), Vector::new(15.0, 20.0), true),
+        ("polygon", Box::new(|b| b.polygon(&[Vector::new(10.0, 10.0), Vector::new(30.0, 10.0), Vector::new(20.0, 30.0)])),
+            Vector::new(10.0, 10.0), true),
+        ("regular_polygon", Box::new(|b| b.regular_polygon(Vector::new(50.0, 50.0), 10.0, 6, 0.0)), Vector::new(60.0, 50.0), true),
+        ("star", Box::new(|b| b.star(Vector::new(50.0, 50.0), 10.0, 5.0, 5, -0.5 * std::f32::consts::PI)),
+            Vector::new(50.0, 40.0), true),
+        ("superellipse", Box::new(|b| b.superellipse(Vector::new(50.0, 50.0), Vector::new(20.0, 10.0), 4.0)),
+            Vector::new(70.0, 50.0), true),
+    ];
+    for (name, shape, start, positive) in shapes {
+        let mut builder = PathBuilder::<O>::new();
+        builder.move_to(Vector::new(0.0, 0.0));
+        builder.line_to(Vector::new(5.0, 5.0));
+        shape(&mut builder);
+        let pos = builder.pos().unwrap();
+        assert!((pos - start).length() < 1e-4, "{}: current point {:?}, expected {:?}", name, pos, start);
+        // continues with a new contour at the start point
+        builder.line_to(Vector::new(0.0, 100.0));
+
+        let events: Vec<PathEvent> = builder.into_outline().events().collect();
+        let moves: Vec<usize> = (0 .. events.len()).filter(|&i| matches!(events[i], PathEvent::MoveTo(_))).collect();
+        assert_eq!(moves.len(), 3, "{}: {:?}", name, events);
+        let contour = &events[moves[1] .. moves[2]];
+        match contour[0] {
+            PathEvent::MoveTo(p) => assert!((p - start).length() < 1e-4, "{}: starts at {:?}", name, p),
+            _ => unreachable!()
+        }
+        assert_eq!(contour.last(), Some(&PathEvent::Close), "{}: not closed", name);
+        assert_eq!(endpoint_area(contour) > 0.0, positive, "{}: wrong direction", name);
+        match events[moves[2] ..] {
+            [PathEvent::MoveTo(p), PathEvent::LineTo(q)] => {
+                assert!((p - start).length() < 1e-4, "{}: next contour starts at {:?}", name, p);
+                assert_eq!(q, Vector::new(0.0, 100.0));
+            }
+            ref rest => panic!("{}: unexpected {:?}", name, rest),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use vector::*;
+use vector::flatten::{quadratic_segments, cubic_segments, MAX_SEGMENTS};
+
+fn cubic_point(p0: Vector, c1: Vector, c2: Vector, p1: Vector, t: f32) -> Vector {
+    let s = 1.0 - t;
+    p0 * (s * s * s) + c1 * (3.0 * s * s * t) + c2 * (3.0 * s * t * t) + p1 * (t * t * t)
+}
+
+fn segment_distance(p: Vector, a: Vector, b: Vector) -> f32 {
+    let ab = b - a;
+    let t = if ab.square_length() > 0.0 { ((p - a).dot(ab) / ab.square_length()).clamp(0.0, 1.0) } else { 0.0 };
+    (a + ab * t - p).length()
+}
+
+fn flattened_points(outline: &Path, tolerance: f32) -> Vec<Vector> {
+    let flat: Path = outline.flatten(tolerance);
+    flat.events().map(|e| match e {
+        PathEvent::MoveTo(p) | PathEvent::LineTo(p) => p,
+        e => panic!("unexpected {:?}", e),
+    }).collect()
+}
+
+/// The largest distance between the curve and the polyline, sampling each segment's piece of the curve.
+fn max_error(points: &[Vector], curve: impl Fn(f32) -> Vector) -> f32 {
+    let n = points.len() - 1;
+    let mut error: f32 = 0.0;
+    for (i, w) in points.windows(2).enumerate() {
+        for k in 0 ..= 16 {
+            let t = (i as f32 + k as f32 / 16.0) / n as f32;
+            error = error.max(segment_distance(curve(t), w[0], w[1]));
+        }
+    }
+    error
+}
+
+#[test]
+fn cubic_error_is_within_tolerance() {
+    let curves = [
+        [(0.0, 0.0), (0.0, 100.0), (100.0, 100.0), (100.0, 0.0)],
+        [(0.0, 0.0), (300.0, 50.0), (-200.0, 50.0), (100.0, 0.0)],
+        [(10.0, 10.0), (11.0, 12.0), (12.0, 9.0), (13.0, 10.0)],
+    ];
+    for &tolerance in &[1.0, 0.1, 0.01] {
+        for c in &curves {
+            let v = |i: usize| Vector::new(c[i].0, c[i].1);
+            let (p0, c1, c2, p1) = (v(0), v(1), v(2), v(3));
+            let mut builder = PathBuilder::<Path>::new();
+            builder.move_to(p0);
+            builder.cubic_curve_to(c1, c2, p1);
+            let points = flattened_points(&builder.into_outline(), tolerance);
+            assert_eq!(points.len() - 1, cubic_segments(p0, c1, c2, p1, tolerance));
+            let error = max_error(&points, |t| cubic_point(p0, c1, c2, p1, t));
+            assert!(error <= tolerance * 1.001, "error {} > {}", error, tolerance);
+        }
+    }
+}
+
+#[test]
+fn quadratic_error_is_within_tolerance() {
+    let (p0, c, p1) = (Vector::new(0.0, 0.0), Vector::new(50.0, 200.0), Vector::new(100.0, 0.0));
+    for &tolerance in &[1.0, 0.1, 0.01] {
+        let mut builder = PathBuilder::<Path>::new();
+        builder.move_to(p0);
+        builder.quadratic_curve_to(c, p1);
+        let points = flattened_points(&builder.into_outline(), tolerance);
+        assert_eq!(points.len() - 1, quadratic_segments(p0, c, p1, tolerance));
+        let error = max_error(&points, |t| {
+            let s = 1.0 - t;
+            p0 * (s * s) + c * (2.0 * s * t) + p1 * (t * t)
+        });
+        assert!(error <= tolerance * 1.001, "error {} > {}", error, tolerance);
+    }
+}
+
+#[test]
+fn segment_count_is_capped() {
+    let (p0, c, p1) = (Vector::new(-3e38, 0.0), Vector::new(0.0, 3e38), Vector::new(3e38, 0.0));
+    assert_eq!(quadratic_segments(p0, c, p1, 1e-6), MAX_SEGMENTS);
+    assert_eq!(cubic_segments(p0, c, c, p1, 1e-6), MAX_SEGMENTS);
+    // degenerate curves need a single line
+    let q = Vector::new(1.0, 2.0);
+    assert_eq!(cubic_segments(q, q, q, q, 1e-6), 1);
+}
+
+#[test]
+fn segment_count_scales_with_the_curve() {
+    // the second differences of these curves do not fit into f32 when squared
+    let v = |x: f32, y: f32, scale: f32| Vector::new(x * scale, y * scale);
+    for &scale in &[1.0, 1e20, 1e37] {
+        assert_eq!(quadratic_segments(v(0.0, 0.0, scale), v(1.0, 2.0, scale), v(2.0, 0.0, scale), 0.001 * scale), 32);
+        assert_eq!(cubic_segments(v(-1.0, 0.0, scale), v(0.0, 1.0, scale), v(0.0, -1.0, scale), v(1.0, 0.0, scale), 0.01 * scale), 16);
+    }
+}
+
+#[test]
+#[should_panic]
+fn zero_tolerance_is_rejected() {
+    quadratic_segments(Vector::new(0.0, 0.0), Vector::new(1.0, 1.0), Vector::new(2.0, 0.0), 0.0);
+}
+
+#[test]
+#[should_panic]
+fn nan_tolerance_is_rejected() {
+    let path: Path = PathBuilder::<Path>::new().into_outline();
+    let _: Path = path.flatten(f32::NAN);
+}
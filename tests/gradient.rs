@@ -0,0 +1,97 @@
+use vector::*;
+
+const RED: Rgba8 = (255, 0, 0, 255);
+const GREEN: Rgba8 = (0, 255, 0, 51);
+const BLUE: Rgba8 = (0, 0, 255, 0);
+
+fn stops() -> Vec<GradientStop> {
+    vec![GradientStop::new(0.0, RED), GradientStop::new(0.4, GREEN), GradientStop::new(1.0, BLUE)]
+}
+
+#[test]
+fn reflect_as_repeat() {
+    let mirrored = vec![
+        GradientStop::new(0.0, RED), GradientStop::new(0.2, GREEN), GradientStop::new(0.5, BLUE),
+        GradientStop::new(0.5, BLUE), GradientStop::new(0.8, GREEN), GradientStop::new(1.0, RED),
+    ];
+
+    let mut linear = LinearGradient::new(Vector::new(1.0, 2.0), Vector::new(3.0, 2.0), stops());
+    linear.spread = Spread::Reflect;
+    assert_eq!(linear.reflect_as_repeat(), LinearGradient {
+        start: Vector::new(1.0, 2.0),
+        end: Vector::new(5.0, 2.0),
+        stops: mirrored.clone(),
+        spread: Spread::Repeat,
+    });
+
+    let mut radial = RadialGradient::new(Vector::new(2.0, 1.0), 3.0, stops());
+    radial.focus = Vector::new(1.0, 1.0);
+    radial.spread = Spread::Reflect;
+    assert_eq!(radial.reflect_as_repeat(), RadialGradient {
+        center: Vector::new(3.0, 1.0),
+        radius: 6.0,
+        focus: Vector::new(1.0, 1.0),
+        stops: mirrored,
+        spread: Spread::Repeat,
+    });
+
+    // the other spreads are left alone
+    for &spread in &[Spread::Pad, Spread::Repeat] {
+        linear.spread = spread;
+        assert_eq!(linear.reflect_as_repeat(), linear);
+        radial.spread = spread;
+        assert_eq!(radial.reflect_as_repeat(), radial);
+    }
+}
+
+#[cfg(feature = "impl_svg")]
+#[test]
+fn svg_gradients() {
+    use pathfinder_content::outline::Outline as PaOutline;
+
+    let mut linear = LinearGradient::new(Vector::new(0.0, 0.0), Vector::new(10.0, 0.0), stops());
+    linear.spread = Spread::Reflect;
+    let mut radial = RadialGradient::new(Vector::new(5.0, 5.0), 5.0, stops());
+    radial.focus = Vector::new(4.0, 4.5);
+    radial.spread = Spread::Repeat;
+    let padded = LinearGradient::new(Vector::new(0.0, 0.0), Vector::new(0.0, 10.0), stops());
+
+    let mut svg = Svg::new(Vector::new(10.0, 10.0));
+    let style = svg.build_style(PathStyle {
+        fill: Some(Paint::LinearGradient(linear)),
+        stroke: Some((Paint::RadialGradient(radial), LineStyle::default(1.0))),
+        fill_rule: FillRule::NonZero,
+    });
+    let padded = svg.build_style(PathStyle { fill: Some(Paint::LinearGradient(padded)), stroke: None, fill_rule: FillRule::NonZero });
+    let mut builder = PathBuilder::<PaOutline>::new();
+    builder.rect(Rect::new(Vector::new(1.0, 1.0), Vector::new(8.0, 8.0)));
+    let square = builder.into_outline();
+    svg.draw_path(square.clone(), &style, None);
+    svg.draw_path(square, &padded, None);
+    let out = svg.finish();
+
+    // the stops with their offsets and opacities
+    let stops = [
+        "<stop offset=\"0\" stop-color=\"#ff0000\" />",
+        "<stop offset=\"0.4\" stop-color=\"#00ff00\" stop-opacity=\"0.2\" />",
+        "<stop offset=\"1\" stop-color=\"#0000ff\" stop-opacity=\"0\" />",
+    ];
+    let gradient = |open: &str, close: &str| format!("{}\n{}\n{}\n", open, stops.join("\n"), close);
+    assert!(out.contains(&gradient(
+        "<linearGradient id=\"grad_0\" gradientUnits=\"userSpaceOnUse\" x1=\"0\" y1=\"0\" x2=\"10\" y2=\"0\" spreadMethod=\"reflect\">",
+        "</linearGradient>"
+    )), "{}", out);
+    assert!(out.contains(&gradient(
+        "<radialGradient id=\"grad_1\" gradientUnits=\"userSpaceOnUse\" cx=\"5\" cy=\"5\" r=\"5\" fx=\"4\" fy=\"4.5\" spreadMethod=\"repeat\">",
+        "</radialGradient>"
+    )), "{}", out);
+    assert!(out.contains(&gradient(
+        "<linearGradient id=\"grad_2\" gradientUnits=\"userSpaceOnUse\" x1=\"0\" y1=\"0\" x2=\"0\" y2=\"10\" spreadMethod=\"pad\">",
+        "</linearGradient>"
+    )), "{}", out);
+
+    let paths: Vec<&str> = out.lines().filter(|l| l.starts_with("<path")).collect();
+    assert_eq!(paths.len(), 2);
+    assert!(paths[0].starts_with("<path style=\"fill: url(#grad_0); stroke: url(#grad_1); stroke-width: 1; \""), "{}", paths[0]);
+    assert!(paths[1].starts_with("<path style=\"fill: url(#grad_2); \""), "{}", paths[1]);
+}
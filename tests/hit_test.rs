@@ -0,0 +1,135 @@
+use vector::*;
+use vector::hit_test::{winding_number, contains, stroke_contains};
+
+fn polygon(builder: &mut PathBuilder<Path>, points: &[(f32, f32)]) {
+    builder.move_to(Vector::new(points[0].0, points[0].1));
+    for &(x, y) in &points[1 ..] {
+        builder.line_to(Vector::new(x, y));
+    }
+    builder.close();
+}
+
+fn open(points: &[(f32, f32)]) -> Path {
+    let mut builder = PathBuilder::new();
+    builder.move_to(Vector::new(points[0].0, points[0].1));
+    for &(x, y) in &points[1 ..] {
+        builder.line_to(Vector::new(x, y));
+    }
+    builder.into_outline()
+}
+
+// (NonZero, EvenOdd) at `p`
+fn rules(path: &Path, p: (f32, f32)) -> (bool, bool) {
+    let p = Vector::new(p.0, p.1);
+    (contains(path, p, FillRule::NonZero, 0.01), contains(path, p, FillRule::EvenOdd, 0.01))
+}
+
+#[test]
+fn self_overlapping_contour() {
+    // a pentagram, the pentagon in the middle is surrounded twice
+    let mut builder = PathBuilder::new();
+    let points: Vec<(f32, f32)> = (0 .. 5).map(|i| {
+        let angle = i as f32 * 4.0 * std::f32::consts::PI / 5.0;
+        (10.0 * angle.sin(), -10.0 * angle.cos())
+    }).collect();
+    polygon(&mut builder, &points);
+    let star = builder.into_outline();
+
+    assert_eq!(winding_number(&star, Vector::new(0.0, 0.0), 0.01).abs(), 2);
+    assert_eq!(rules(&star, (0.0, 0.0)), (true, false));
+    // in a tip
+    assert_eq!(winding_number(&star, Vector::new(0.0, -8.0), 0.01).abs(), 1);
+    assert_eq!(rules(&star, (0.0, -8.0)), (true, true));
+    assert_eq!(rules(&star, (9.0, 9.0)), (false, false));
+}
+
+#[test]
+fn nested_contours() {
+    let outer = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+    let inner = [(3.0, 3.0), (7.0, 3.0), (7.0, 7.0), (3.0, 7.0)];
+    let inner_reversed: Vec<(f32, f32)> = inner.iter().rev().cloned().collect();
+
+    // opposite direction: a hole for both rules
+    let mut builder = PathBuilder::new();
+    polygon(&mut builder, &outer);
+    polygon(&mut builder, &inner_reversed);
+    let hole = builder.into_outline();
+    assert_eq!(winding_number(&hole, Vector::new(5.0, 5.0), 0.01), 0);
+    assert_eq!(rules(&hole, (5.0, 5.0)), (false, false));
+    assert_eq!(rules(&hole, (1.0, 5.0)), (true, true));
+
+    // same direction: only EvenOdd leaves a hole
+    let mut builder = PathBuilder::new();
+    polygon(&mut builder, &outer);
+    polygon(&mut builder, &inner);
+    let doubled = builder.into_outline();
+    assert_eq!(winding_number(&doubled, Vector::new(5.0, 5.0), 0.01).abs(), 2);
+    assert_eq!(rules(&doubled, (5.0, 5.0)), (true, false));
+    assert_eq!(rules(&doubled, (1.0, 5.0)), (true, true));
+    assert_eq!(rules(&doubled, (11.0, 5.0)), (false, false));
+}
+
+#[test]
+fn curves_are_followed() {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.circle(Vector::new(0.0, 0.0), 10.0);
+    let circle = builder.into_outline();
+
+    // outside of the diamond through the end points of the four curves by 1.4,
+    // far more than the tolerance
+    let p = Vector::new(6.0, 6.0);
+    assert!(p.x() + p.y() - 10.0 > 1.0);
+    assert!(contains(&circle, p, FillRule::NonZero, 0.1));
+    // 0.1 inside and outside of the circle
+    let diagonal = Vector::new(1.0, 1.0) * std::f32::consts::FRAC_1_SQRT_2;
+    assert!(contains(&circle, diagonal * 9.9, FillRule::NonZero, 0.01));
+    assert!(!contains(&circle, diagonal * 10.1, FillRule::NonZero, 0.01));
+}
+
+fn style(cap: LineCap, join: LineJoin) -> LineStyle {
+    LineStyle { cap, join, ..LineStyle::default(2.0) }
+}
+
+#[test]
+fn stroke_caps() {
+    let line = open(&[(0.0, 0.0), (10.0, 0.0)]);
+    let hit = |cap, x, y| stroke_contains(&line, Vector::new(x, y), &style(cap, LineJoin::Bevel), 0.01);
+
+    // next to the line
+    for &cap in &[LineCap::Butt, LineCap::Square, LineCap::Round] {
+        assert!(hit(cap, 5.0, 0.9));
+        assert!(!hit(cap, 5.0, 1.1));
+    }
+    // beyond the end, within half the width
+    assert!(!hit(LineCap::Butt, -0.5, 0.0));
+    assert!(hit(LineCap::Square, -0.5, 0.0));
+    assert!(hit(LineCap::Round, -0.5, 0.0));
+    assert!(!hit(LineCap::Butt, 10.5, 0.0));
+    assert!(hit(LineCap::Square, 10.5, 0.0));
+    // in the corner of the square cap, outside of the round one
+    assert!(hit(LineCap::Square, -0.8, 0.8));
+    assert!(!hit(LineCap::Round, -0.8, 0.8));
+    assert!(!hit(LineCap::Square, -1.1, 0.0));
+}
+
+#[test]
+fn stroke_joins() {
+    // a right angle at (10, 0), the outer corner of the miter is at (11, -1)
+    let corner = open(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+    let hit = |join, x, y| stroke_contains(&corner, Vector::new(x, y), &style(LineCap::Butt, join), 0.01);
+
+    // the miter is √2 times the width
+    assert!(hit(LineJoin::Miter(1.5), 10.8, -0.8));
+    assert!(!hit(LineJoin::Miter(1.5), 11.1, -1.1));
+    // below the limit it falls back to a bevel
+    assert!(!hit(LineJoin::Miter(1.4), 10.8, -0.8));
+    assert!(!hit(LineJoin::Bevel, 10.8, -0.8));
+    assert!(!hit(LineJoin::Round, 10.8, -0.8));
+    // inside of the bevel and the round join
+    for &join in &[LineJoin::Miter(1.4), LineJoin::Miter(1.5), LineJoin::Bevel, LineJoin::Round] {
+        assert!(hit(join, 10.4, -0.4));
+    }
+    // between the bevel and the arc
+    assert!(hit(LineJoin::Round, 10.65, -0.65));
+    assert!(!hit(LineJoin::Bevel, 10.65, -0.65));
+}
@@ -0,0 +1,83 @@
+use vector::*;
+
+// a surface that only implements the required methods and counts what is drawn
+struct Counter {
+    draws: usize,
+}
+impl Surface for Counter {
+    type Outline = Path;
+    type Style = ();
+    type ClipPath = ();
+    type Image = ();
+
+    fn new(_size: Vector) -> Self {
+        Counter { draws: 0 }
+    }
+    fn build_style(&mut self, _style: PathStyle<Self>) {}
+    fn draw_path(&mut self, _path: Path, _style: &(), _clip: Option<&()>) {
+        self.draws += 1;
+    }
+    fn clip_path(&mut self, _path: Path, _fill_rule: FillRule) {}
+    fn texture_with_stride(&mut self, _width: u32, _height: u32, _data: &[u8], _stride: usize, _format: PixelFormat)
+        -> Result<(), TextureError>
+    {
+        Ok(())
+    }
+}
+
+fn square() -> Path {
+    let mut builder = PathBuilder::new();
+    builder.rect(Rect::new(Vector::new(1.0, 1.0), Vector::new(2.0, 2.0)));
+    builder.into_outline()
+}
+
+#[test]
+fn layers_default_to_drawing_directly() {
+    let mut recording = Recording::new(Vector::new(10.0, 10.0));
+    let style = recording.build_style(PathStyle { fill: Some(Paint::black()), stroke: None, fill_rule: FillRule::NonZero });
+    let clip = recording.clip_path(square(), FillRule::NonZero);
+    recording.draw_path(square(), &style, None);
+    let layer = recording.push_layer(0.5, BlendMode::Multiply, Some(&clip));
+    recording.draw_path(square(), &style, None);
+    recording.pop_layer(layer);
+
+    let mut counter = Counter::new(Vector::new(10.0, 10.0));
+    recording.replay(&mut counter).unwrap();
+    assert_eq!(counter.draws, 2);
+}
+
+#[cfg(feature = "impl_svg")]
+#[test]
+fn svg_layers() {
+    use pathfinder_content::outline::Outline as PaOutline;
+
+    let mut svg = Svg::new(Vector::new(10.0, 10.0));
+    let style = svg.build_style(PathStyle { fill: Some(Paint::black()), stroke: None, fill_rule: FillRule::NonZero });
+    let clip = svg.clip_path(square().to_outline::<PaOutline>(), FillRule::EvenOdd);
+    let outer = svg.push_layer(0.5, BlendMode::Multiply, Some(&clip));
+    svg.draw_path(square().to_outline(), &style, None);
+    let inner = svg.push_layer(0.25, BlendMode::Normal, None);
+    svg.draw_path(square().to_outline(), &style, Some(&clip));
+    svg.pop_layer(inner);
+    svg.pop_layer(outer);
+    let plain = svg.push_layer(1.0, BlendMode::Screen, None);
+    svg.pop_layer(plain);
+    let out = svg.finish();
+
+    assert!(out.contains("<clipPath id=\"clip_0\"><path clip-rule=\"evenodd\" "), "{}", out);
+    // every element is on its own line
+    let tags: Vec<&str> = out.lines().map(|l| l.split(|c| c == ' ' || c == '>').next().unwrap()).collect();
+    assert_eq!(tags, ["<svg", "<clipPath", "<g", "<path", "<g", "<path", "</g", "</g", "<g", "</g", "</svg"]);
+
+    let groups: Vec<&str> = out.lines().filter(|l| l.starts_with("<g")).collect();
+    assert_eq!(groups, [
+        "<g opacity=\"0.5\" style=\"mix-blend-mode: multiply\" clip-path=\"url(#clip_0)\">",
+        "<g opacity=\"0.25\">",
+        // full opacity is left out
+        "<g style=\"mix-blend-mode: screen\">",
+    ]);
+    let paths: Vec<&str> = out.lines().filter(|l| l.starts_with("<path")).collect();
+    assert_eq!(paths.len(), 2);
+    assert!(!paths[0].contains("clip-path"));
+    assert!(paths[1].contains(" clip-path=\"url(#clip_0)\" "));
+}
@@ -0,0 +1,19 @@
+use vector::{LineStyle, DashArray};
+
+#[test]
+fn line_style_is_copy() {
+    let style = LineStyle::default(2.0).dashed(&[3.0, 1.0], 0.5);
+    let copy = style;
+    assert_eq!(style, copy);
+    assert_eq!(&*copy.dash_array, &[3.0, 1.0]);
+}
+
+#[test]
+fn invalid_dash_arrays_are_solid() {
+    // negative or non-finite values make the whole list invalid, like in SVG
+    assert!(!LineStyle::default(1.0).dashed(&[3.0, -1.0], 0.0).is_dashed());
+    assert!(!LineStyle::default(1.0).dashed(&[f32::NAN], 0.0).is_dashed());
+    assert!(!LineStyle::default(1.0).dashed(&[0.0, 0.0], 0.0).is_dashed());
+    assert!(DashArray::new(&[1.0; DashArray::CAPACITY + 1]).is_none());
+    assert!(DashArray::new(&[1.0; DashArray::CAPACITY]).is_some());
+}
@@ -0,0 +1,62 @@
+use vector::*;
+use std::f32::consts::PI;
+
+fn circle(radius: f32) -> Path {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.circle(Vector::new(0.0, 0.0), radius);
+    builder.into_outline()
+}
+
+#[test]
+fn line_length_and_positions() {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.move_to(Vector::new(0.0, 0.0));
+    builder.line_to(Vector::new(30.0, 40.0));
+    builder.line_to(Vector::new(30.0, 0.0));
+    let measure = PathMeasure::new(&builder.into_outline(), 0.01);
+    assert!((measure.length() - 90.0).abs() < 1e-4);
+
+    let (p, t) = measure.position_and_tangent(25.0).unwrap();
+    assert!((p - Vector::new(15.0, 20.0)).length() < 1e-4);
+    assert!((t - Vector::new(0.6, 0.8)).length() < 1e-4);
+    let (p, t) = measure.position_and_tangent(70.0).unwrap();
+    assert!((p - Vector::new(30.0, 20.0)).length() < 1e-4);
+    assert!((t - Vector::new(0.0, -1.0)).length() < 1e-4);
+    // clamped to the ends
+    assert_eq!(measure.position_and_tangent(-5.0).unwrap().0, Vector::new(0.0, 0.0));
+    assert!((measure.position_and_tangent(100.0).unwrap().0 - Vector::new(30.0, 0.0)).length() < 1e-4);
+}
+
+#[test]
+fn circle_length() {
+    // the four cubic curves bulge out by up to 0.027% of the radius, which makes
+    // them about 0.014% longer than the circle
+    let curve_length = 62.840_668;
+    assert!((curve_length - 20.0 * PI) / (20.0 * PI) < 1.5e-4);
+    for &tolerance in &[1.0, 0.1, 0.01] {
+        let measure = PathMeasure::new(&circle(10.0), tolerance);
+        assert!((measure.length() - curve_length).abs() < 1e-3, "{} at {}", measure.length(), tolerance);
+    }
+}
+
+#[test]
+fn circle_positions() {
+    let radius = 10.0;
+    let measure = PathMeasure::new(&circle(radius), 0.01);
+    let length = measure.length();
+    for i in 0 .. 16 {
+        let distance = length * i as f32 / 16.0;
+        let (p, t) = measure.position_and_tangent(distance).unwrap();
+        // on the circle, with the tangent perpendicular to the radius
+        assert!((p.length() - radius).abs() < 0.01, "{:?}", p);
+        assert!(p.dot(t).abs() < 0.01 * radius, "{:?} {:?}", p, t);
+        // evenly spaced along the circle, which runs in the direction of decreasing angles
+        let angle = p.y().atan2(p.x());
+        let start = measure.position_and_tangent(0.0).unwrap().0;
+        let start_angle = start.y().atan2(start.x());
+        let traveled = (start_angle - angle).rem_euclid(2.0 * PI);
+        let expected = (2.0 * PI * i as f32 / 16.0).rem_euclid(2.0 * PI);
+        let diff = (traveled - expected).abs();
+        assert!(diff.min(2.0 * PI - diff) < 0.002, "{} vs {}", traveled, expected);
+    }
+}
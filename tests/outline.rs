@@ -0,0 +1,102 @@
+mod common;
+
+use vector::*;
+use PathEvent::*;
+use common::approx_rect;
+
+fn v(x: f32, y: f32) -> Vector {
+    Vector::new(x, y)
+}
+
+// an open contour of curves, a closed contour whose last segment is a curve back to the start
+// and a closed contour that ends with a curve somewhere else
+fn events() -> Vec<PathEvent> {
+    vec![
+        MoveTo(v(0., 0.)),
+        QuadTo(v(5., 10.), v(10., 0.)),
+        CubicTo(v(12., 5.), v(18., 5.), v(20., 0.)),
+        LineTo(v(30., 0.)),
+
+        MoveTo(v(0., 20.)),
+        LineTo(v(10., 20.)),
+        CubicTo(v(10., 30.), v(0., 30.), v(0., 20.)),
+        Close,
+
+        MoveTo(v(20., 20.)),
+        CubicTo(v(25., 15.), v(30., 25.), v(30., 30.)),
+        QuadTo(v(25., 35.), v(20., 30.)),
+        Close,
+    ]
+}
+
+fn path() -> Path {
+    let mut path = Path::new();
+    for event in events() {
+        match event {
+            MoveTo(p) => path.move_to(p),
+            LineTo(p) => path.line_to(p),
+            QuadTo(c, p) => path.quadratic_curve_to(c, p),
+            CubicTo(c1, c2, p) => path.cubic_curve_to(c1, c2, p),
+            Close => path.close(),
+        }
+    }
+    path
+}
+
+fn check_round_trip<O: Outline>() {
+    let outline: O = path().to_outline();
+    assert_eq!(outline.events().collect::<Vec<_>>(), events());
+
+    let again: O = outline.to_outline();
+    assert_eq!(again.events().collect::<Vec<_>>(), events());
+
+    let back: Path = outline.to_outline();
+    assert_eq!(back, path());
+}
+
+#[test]
+fn path_round_trip() {
+    assert_eq!(path().events().collect::<Vec<_>>(), events());
+    check_round_trip::<Path>();
+}
+
+#[cfg(feature = "impl_pathfinder")]
+#[test]
+fn pathfinder_round_trip() {
+    check_round_trip::<pathfinder_content::outline::Outline>();
+}
+
+#[cfg(feature = "impl_raqote")]
+#[test]
+fn raqote_round_trip() {
+    check_round_trip::<raqote::Path>();
+}
+
+fn bounds(events: &[PathEvent]) -> Rect {
+    let outline: Path = events.iter().fold(Path::new(), |mut path, event| {
+        match *event {
+            MoveTo(p) => path.move_to(p),
+            QuadTo(c, p) => path.quadratic_curve_to(c, p),
+            CubicTo(c1, c2, p) => path.cubic_curve_to(c1, c2, p),
+            _ => unreachable!(),
+        }
+        path
+    });
+    outline.bounding_box().unwrap()
+}
+
+#[test]
+fn tight_bounding_box() {
+    // the control points reach y = 10, the curves only 5 and 7.5
+    let r = bounds(&[MoveTo(v(0., 0.)), QuadTo(v(5., 10.), v(10., 0.))]);
+    assert!(approx_rect(r, (0., 0.), (10., 5.), 1e-4), "{:?}", r);
+
+    let r = bounds(&[MoveTo(v(0., 0.)), CubicTo(v(0., 10.), v(10., 10.), v(10., 0.))]);
+    assert!(approx_rect(r, (0., 0.), (10., 7.5), 1e-4), "{:?}", r);
+
+    // x(t) = 30 t (1 - t) (1 - 2t) has its extremes ±5/√3 at t = (3 ∓ √3) / 6,
+    // both outside the end points and inside the control points at ±10
+    let r = bounds(&[MoveTo(v(0., 0.)), CubicTo(v(10., 10.), v(-10., 10.), v(0., 0.))]);
+    let x = 5. / 3f32.sqrt();
+    assert!(approx_rect(r, (-x, 0.), (2. * x, 7.5), 1e-4), "{:?}", r);
+}
@@ -0,0 +1,198 @@
+use vector::*;
+
+fn events(path: &Path) -> Vec<PathEvent> {
+    path.events().collect()
+}
+
+#[test]
+fn path_data_overflow_is_an_error() {
+    // each number is finite, the absolute coordinate is not
+    let cases = ["m3e38 0 l3e38 0", "M3e38 0 h3e38", "M0 0 v-3e38 v-3e38", "M3e38 3e38 c1 1 1 1 3e38 0", "M0 0 a3e38 3e38 0 0 1 3e38 3e38"];
+    for data in &cases {
+        let mut builder = PathBuilder::<Path>::new();
+        let error = parse_path_data(data, &mut builder).unwrap_err();
+        assert_eq!(error.kind, PathDataErrorKind::OutOfRange, "{}", data);
+    }
+    // the position points at the failing command
+    let mut builder = PathBuilder::<Path>::new();
+    let error = parse_path_data("m3e38 0 l3e38 0", &mut builder).unwrap_err();
+    assert_eq!(error.position, 9);
+    // everything before is kept
+    assert_eq!(builder.pos(), Some(Vector::new(3e38, 0.0)));
+}
+
+#[test]
+fn default_builder_panics() {
+    let result = std::panic::catch_unwind(|| {
+        let mut builder = PathBuilder::<Path>::default();
+        builder.line_to(Vector::new(1.0, 1.0));
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn try_relative_commands() {
+    let mut builder = PathBuilder::<Path>::new();
+    assert_eq!(builder.try_rel_line_to(Vector::new(1.0, 0.0)), Err(PathError::NoStartPoint));
+    builder.try_move_to(Vector::new(3e38, 0.0)).unwrap();
+    assert!(matches!(builder.try_rel_line_to(Vector::new(3e38, 0.0)), Err(PathError::NonFinite(_))));
+    assert!(matches!(builder.try_rel_horizontal_line_to(3e38), Err(PathError::NonFinite(_))));
+    assert!(matches!(builder.try_rel_smooth_quadratic_curve_to(Vector::new(3e38, 0.0)), Err(PathError::NonFinite(_))));
+    assert!(matches!(builder.try_rel_smooth_cubic_curve_to(Vector::new(0.0, 0.0), Vector::new(3e38, 0.0)), Err(PathError::NonFinite(_))));
+    builder.try_rel_line_to(Vector::new(-1e38, 1.0)).unwrap();
+    builder.try_rel_vertical_line_to(1.0).unwrap();
+    assert_eq!(builder.pos(), Some(Vector::new(3e38f32 - 1e38, 2.0)));
+    // failed commands add nothing
+    assert_eq!(events(&builder.into_outline()).len(), 3);
+}
+
+#[test]
+fn try_smooth_commands_reflect() {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.move_to(Vector::new(0.0, 0.0));
+    builder.try_cubic_curve_to(Vector::new(0.0, 1.0), Vector::new(1.0, 1.0), Vector::new(2.0, 0.0)).unwrap();
+    builder.try_rel_smooth_cubic_curve_to(Vector::new(1.0, -1.0), Vector::new(2.0, 0.0)).unwrap();
+    match events(&builder.into_outline())[2] {
+        PathEvent::CubicTo(c1, c2, p) => {
+            assert_eq!(c1, Vector::new(3.0, -1.0));
+            assert_eq!(c2, Vector::new(3.0, -1.0));
+            assert_eq!(p, Vector::new(4.0, 0.0));
+        }
+        ref e => panic!("unexpected {:?}", e),
+    }
+}
+
+#[test]
+fn try_arcs() {
+    let mut builder = PathBuilder::<Path>::new();
+    assert_eq!(builder.try_arc_to(Vector::splat(1.0), 0.0, false, true, Vector::new(1.0, 1.0)), Err(PathError::NoStartPoint));
+    builder.move_to(Vector::new(0.0, 0.0));
+    // finite arguments, but the center of the arc is out of range
+    assert!(matches!(builder.try_arc_to(Vector::splat(3e38), 0.0, false, true, Vector::new(3e38, 3e38)), Err(PathError::NonFinite(_))));
+    assert!(matches!(builder.try_arc(Vector::new(3e38, 0.0), Vector::splat(3e38), 0.0, 0.0, 1.0, true), Err(PathError::NonFinite(_))));
+    assert!(matches!(builder.try_arc(Vector::new(f32::NAN, 0.0), Vector::splat(1.0), 0.0, 0.0, 1.0, true), Err(PathError::NonFinite(_))));
+    assert_eq!(builder.pos(), Some(Vector::new(0.0, 0.0)));
+
+    builder.try_rel_arc_to(Vector::splat(1.0), 0.0, false, true, Vector::new(2.0, 0.0)).unwrap();
+    assert_eq!(builder.pos(), Some(Vector::new(2.0, 0.0)));
+    builder.try_arc(Vector::new(2.0, 2.0), Vector::splat(2.0), 0.0, -0.5 * std::f32::consts::PI, 0.0, true).unwrap();
+    assert!((builder.pos().unwrap() - Vector::new(4.0, 2.0)).length() < 1e-5);
+}
+
+fn is_finite(path: &Path) -> bool {
+    let finite = |p: Vector| p.x().is_finite() && p.y().is_finite();
+    path.events().all(|e| match e {
+        PathEvent::MoveTo(p) | PathEvent::LineTo(p) => finite(p),
+        PathEvent::QuadTo(c, p) => finite(c) && finite(p),
+        PathEvent::CubicTo(c1, c2, p) => finite(c1) && finite(c2) && finite(p),
+        PathEvent::Close => true,
+    })
+}
+
+#[test]
+fn internal_builders_do_not_panic() {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.move_to(Vector::new(3e38, 0.0));
+    builder.line_to(Vector::new(3e38, 1e38));
+    let line = builder.into_outline();
+    // the right side of the stroke is out of range and left out, the left side is kept
+    let stroke: Path = line.stroke_to_fill(&LineStyle::default(3e38), 1.0);
+    assert!(is_finite(&stroke));
+    let r = stroke.bounding_box().unwrap();
+    assert_eq!((r.min_x(), r.max_x(), r.min_y(), r.max_y()), (1.5e38, 1.5e38, 0.0, 1e38));
+
+    let mut builder = PathBuilder::<Path>::new();
+    builder.move_to(Vector::new(-1e18, 0.0));
+    builder.cubic_curve_to(Vector::new(0.0, 1e18), Vector::new(0.0, -1e18), Vector::new(1e18, 0.0));
+    let curve = builder.into_outline();
+
+    let stroke: Path = curve.stroke_to_fill(&LineStyle::default(3e38), 1e16);
+    assert!(is_finite(&stroke));
+    assert!(stroke.events().count() > 0);
+
+    let flat: Path = curve.flatten(1e16);
+    assert!(is_finite(&flat));
+    let flat = events(&flat);
+    assert_eq!(flat.first(), Some(&PathEvent::MoveTo(Vector::new(-1e18, 0.0))));
+    assert_eq!(flat.last(), Some(&PathEvent::LineTo(Vector::new(1e18, 0.0))));
+    assert!(flat[1 ..].iter().all(|e| matches!(e, PathEvent::LineTo(_))));
+
+    let union: Path = curve.boolean(BooleanOp::Union, &line, FillRule::NonZero, 1e16);
+    assert!(is_finite(&union));
+    assert!(union.events().count() > 0);
+
+    // the whole curve
+    let extracted: Path = PathMeasure::new(&curve, 1e16).extract(0.0, f32::INFINITY);
+    assert_eq!(events(&extracted), events(&curve));
+}
+
+type Command = Box<dyn Fn(&mut PathBuilder<Path>) -> Result<(), PathError>>;
+
+// the commands that need a current point, with `x` as one of their coordinates
+fn commands(x: f32) -> Vec<(&'static str, Command, Vector)> {
+    let v = Vector::new(x, 0.0);
+    let one = Vector::splat(1.0);
+    vec![
+        ("line_to", Box::new(move |b| b.try_line_to(v)), v),
+        ("quadratic_curve_to", Box::new(move |b| b.try_quadratic_curve_to(v, one)), v),
+        ("cubic_curve_to", Box::new(move |b| b.try_cubic_curve_to(one, v, one)), v),
+        ("rel_line_to", Box::new(move |b| b.try_rel_line_to(v)), v),
+        ("rel_quadratic_curve_to", Box::new(move |b| b.try_rel_quadratic_curve_to(v, one)), v),
+        ("rel_cubic_curve_to", Box::new(move |b| b.try_rel_cubic_curve_to(one, one, v)), v),
+        ("horizontal_line_to", Box::new(move |b| b.try_horizontal_line_to(x)), v),
+        ("vertical_line_to", Box::new(move |b| b.try_vertical_line_to(x)), Vector::new(0.0, x)),
+        ("rel_horizontal_line_to", Box::new(move |b| b.try_rel_horizontal_line_to(x)), v),
+        ("rel_vertical_line_to", Box::new(move |b| b.try_rel_vertical_line_to(x)), Vector::new(0.0, x)),
+        ("smooth_quadratic_curve_to", Box::new(move |b| b.try_smooth_quadratic_curve_to(v)), v),
+        ("smooth_cubic_curve_to", Box::new(move |b| b.try_smooth_cubic_curve_to(v, one)), v),
+        ("rel_smooth_quadratic_curve_to", Box::new(move |b| b.try_rel_smooth_quadratic_curve_to(v)), v),
+        ("rel_smooth_cubic_curve_to", Box::new(move |b| b.try_rel_smooth_cubic_curve_to(one, v)), v),
+        ("arc_to", Box::new(move |b| b.try_arc_to(one, 0.0, false, true, v)), v),
+        ("rel_arc_to", Box::new(move |b| b.try_rel_arc_to(one, 0.0, false, true, v)), v),
+    ]
+}
+
+#[test]
+fn try_commands_report_errors() {
+    for (name, command, _) in commands(1.0) {
+        let mut builder = PathBuilder::<Path>::new();
+        assert_eq!(command(&mut builder), Err(PathError::NoStartPoint), "{}", name);
+        assert_eq!(builder.pos(), None, "{}", name);
+    }
+    for (name, command, point) in commands(f32::INFINITY) {
+        let mut builder = PathBuilder::<Path>::new();
+        builder.move_to(Vector::new(0.0, 0.0));
+        assert_eq!(command(&mut builder), Err(PathError::NonFinite(point)), "{}", name);
+        assert_eq!(builder.pos(), Some(Vector::new(0.0, 0.0)), "{}", name);
+        assert!(builder.into_outline().events().next().is_none(), "{}", name);
+    }
+    // these start a contour themselves
+    let mut builder = PathBuilder::<Path>::new();
+    let inf = Vector::new(f32::INFINITY, 0.0);
+    assert_eq!(builder.try_move_to(inf), Err(PathError::NonFinite(inf)));
+    assert_eq!(builder.try_rel_move_to(inf), Err(PathError::NonFinite(inf)));
+    assert_eq!(builder.try_arc(inf, Vector::splat(1.0), 0.0, 0.0, 1.0, true), Err(PathError::NonFinite(inf)));
+    assert_eq!(builder.pos(), None);
+    builder.try_rel_move_to(Vector::new(1.0, 2.0)).unwrap();
+    assert_eq!(builder.pos(), Some(Vector::new(1.0, 2.0)));
+}
+
+#[test]
+fn arcs_are_added_completely_or_not_at_all() {
+    // the first quarter of the arc is in range, the second one ends at x = 3.5e38
+    let center = Vector::new(2e38, 0.0);
+    let radius = Vector::splat(1.5e38);
+    let pi = std::f32::consts::PI;
+    for &policy in &[ErrorPolicy::Ignore, ErrorPolicy::MoveToOrigin] {
+        let mut builder = PathBuilder::<Path>::with_policy(policy);
+        builder.move_to(Vector::new(0.0, 0.0));
+        builder.arc(center, radius, 0.0, pi, 2.0 * pi, true);
+        assert_eq!(builder.pos(), Some(Vector::new(0.0, 0.0)), "{:?}", policy);
+        assert!(builder.into_outline().events().next().is_none(), "{:?}", policy);
+    }
+    let mut builder = PathBuilder::<Path>::new();
+    assert!(matches!(builder.try_arc(center, radius, 0.0, pi, 2.0 * pi, true), Err(PathError::NonFinite(_))));
+    // the first quarter alone fits
+    builder.arc(center, radius, 0.0, pi, 1.5 * pi, true);
+    assert!((builder.pos().unwrap() - Vector::new(2e38, -1.5e38)).length() < 1e32);
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "impl_pdf")]
+
+use vector::*;
+use pathfinder_geometry::rect::RectF;
+
+fn rect(origin: Vector, size: Vector) -> Path {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.rect(RectF::new(origin, size));
+    builder.into_outline()
+}
+
+// one char per byte, so offsets into the file stay valid
+fn text(pdf: &[u8]) -> String {
+    pdf.iter().map(|&b| if b.is_ascii() { b as char } else { '?' }).collect()
+}
+
+#[test]
+fn xref_points_at_objects() {
+    let mut pdf = Pdf::new(Vector::new(200.0, 100.0));
+    let style = pdf.build_style(PathStyle {
+        fill: Some(Paint::Solid((255, 0, 0, 128))),
+        stroke: Some((Paint::black(), LineStyle::default(2.0))),
+        fill_rule: FillRule::NonZero,
+    });
+    let image = pdf.texture(1, 1, &[0, 255, 0, 128], PixelFormat::Rgba32).unwrap();
+    let image_style = pdf.build_style(PathStyle {
+        fill: Some(Paint::Image(image, Transform::from_scale(Vector::splat(10.0)))),
+        stroke: None,
+        fill_rule: FillRule::NonZero,
+    });
+    let path = rect(Vector::new(10.0, 10.0), Vector::new(50.0, 30.0));
+    pdf.draw_path(path.clone(), &style, None);
+    pdf.push_layer(0.5, BlendMode::Multiply, None);
+    pdf.draw_path(path, &image_style, None);
+    let out = pdf.finish();
+    let s = text(&out);
+    assert!(s.starts_with("%PDF-1.4\n"));
+    assert!(s.ends_with("%%EOF\n"));
+
+    // startxref points at the xref table
+    let tail = &s[s.rfind("startxref\n").unwrap() + "startxref\n".len() ..];
+    let xref: usize = tail.lines().next().unwrap().parse().unwrap();
+    assert!(s[xref ..].starts_with("xref\n"));
+
+    let mut lines = s[xref ..].lines().skip(1);
+    let mut header = lines.next().unwrap().split(' ');
+    assert_eq!(header.next(), Some("0"));
+    let count: usize = header.next().unwrap().parse().unwrap();
+    // catalog, pages, page, resources, content, image, soft mask, pattern, form
+    assert_eq!(count, 10);
+    assert_eq!(lines.next(), Some("0000000000 65535 f "));
+
+    // every entry is 20 bytes long and points at the start of its object
+    let entries = &s[xref + "xref\n0 10\n".len() ..];
+    for i in 1 .. count {
+        let entry = &entries[20 * i .. 20 * (i + 1)];
+        assert!(entry.ends_with(" 00000 n \n"), "{:?}", entry);
+        let offset: usize = entry[.. 10].parse().unwrap();
+        let object = &s[offset ..];
+        assert!(object.starts_with(&format!("{} 0 obj\n", i)), "object {} at {}", i, offset);
+        let body = &object[object.find('\n').unwrap() .. object.find("endobj\n").unwrap()];
+        assert!(!body.contains(" 0 obj\n"), "object {} is not closed", i);
+    }
+    assert!(entries[20 * count ..].starts_with("trailer\n"));
+    assert!(s.contains(&format!("/Size {} /Root 1 0 R", count)));
+    assert_eq!(s.matches(" 0 obj\n").count(), count - 1);
+}
+
+#[test]
+fn gradient_fallback() {
+    let mut gradient = LinearGradient::new(Vector::new(0.0, 0.0), Vector::new(100.0, 0.0), vec![
+        GradientStop::new(0.0, (255, 0, 0, 128)),
+        GradientStop::new(1.0, (0, 0, 255, 0)),
+    ]);
+    gradient.spread = Spread::Repeat;
+    let mut pdf = Pdf::new(Vector::new(100.0, 100.0));
+    let style = pdf.build_style(PathStyle {
+        fill: Some(Paint::LinearGradient(gradient)),
+        stroke: None,
+        fill_rule: FillRule::NonZero,
+    });
+    pdf.draw_path(rect(Vector::new(0.0, 0.0), Vector::new(100.0, 100.0)), &style, None);
+    let s = text(&pdf.finish());
+
+    // the stops are drawn opaque and the gradient is padded
+    assert!(s.contains("/ShadingType 2"));
+    assert!(s.contains("/C0 [1 0 0] /C1 [0 0 1]"));
+    assert!(s.contains("/Extend [true true]"));
+    assert!(!s.contains("/ExtGState"));
+    assert!(!s.contains("/ca "));
+}
+
+#[test]
+fn non_finite_numbers() {
+    let mut pdf = Pdf::new(Vector::new(f32::INFINITY, 100.0));
+    let mut line = LineStyle { join: LineJoin::Miter(f32::NAN), ..LineStyle::default(f32::NAN) }.dashed(&[1.0, 2.0], 0.0);
+    line.dash_offset = f32::INFINITY;
+    let style = pdf.build_style(PathStyle {
+        fill: Some(Paint::RadialGradient(RadialGradient::new(Vector::new(f32::NAN, 0.0), f32::INFINITY, vec![
+            GradientStop::new(0.0, (255, 0, 0, 255)),
+            GradientStop::new(f32::NAN, (0, 255, 0, 255)),
+            GradientStop::new(1.0, (0, 0, 255, 255)),
+        ]))),
+        stroke: Some((Paint::black(), line)),
+        fill_rule: FillRule::NonZero,
+    });
+    // the path builder rejects these, so build the contour directly
+    let mut contour = <Path as Outline>::Contour::new();
+    contour.move_to(Vector::new(0.0, 0.0));
+    contour.line_to(Vector::new(f32::NAN, 10.0));
+    contour.cubic_curve_to(Vector::new(10.0, f32::NEG_INFINITY), Vector::new(1.0, 1.0), Vector::new(2.0, 2.0));
+    let mut path = Path::empty();
+    path.add_contour(contour);
+    pdf.draw_path(path, &style, None);
+    pdf.push_layer(f32::NAN, BlendMode::Normal, None);
+    pdf.draw_path(rect(Vector::new(0.0, 0.0), Vector::new(10.0, 10.0)), &style, None);
+    let s = text(&pdf.finish());
+
+    for token in s.split(|c: char| c.is_whitespace() || "[]<>/".contains(c)) {
+        assert!(!token.eq_ignore_ascii_case("nan") && !token.contains("inf"), "{:?}", token);
+    }
+}
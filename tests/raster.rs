@@ -0,0 +1,118 @@
+use vector::*;
+
+// straight RGBA pixels to encode
+struct Image(u32, u32, Vec<u8>);
+impl RasterOutput for Image {
+    fn to_rgba8(&self) -> (u32, u32, Vec<u8>) {
+        (self.0, self.1, self.2.clone())
+    }
+}
+
+// the kind, data and crc of every chunk after the signature
+fn chunks(png: &[u8]) -> Vec<([u8; 4], &[u8], u32)> {
+    assert_eq!(&png[.. 8], b"\x89PNG\r\n\x1a\n");
+    let mut rest = &png[8 ..];
+    let mut chunks = vec![];
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = [rest[4], rest[5], rest[6], rest[7]];
+        let data = &rest[8 .. 8 + len];
+        let crc = &rest[8 + len .. 12 + len];
+        chunks.push((kind, data, u32::from_be_bytes([crc[0], crc[1], crc[2], crc[3]])));
+        rest = &rest[12 + len ..];
+    }
+    chunks
+}
+
+// color type from the IHDR and the crc of the IHDR and IDAT chunks
+fn check_png(image: Image, color_type: u8, crcs: [u32; 2]) {
+    let png = image.encode_png();
+    let chunks = chunks(&png);
+    let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _, _)| &kind[..]).collect();
+    assert_eq!(kinds, [&b"IHDR"[..], b"IDAT", b"IEND"]);
+
+    let ihdr = chunks[0].1;
+    assert_eq!(&ihdr[0 .. 8], [&image.0.to_be_bytes()[..], &image.1.to_be_bytes()].concat());
+    assert_eq!(ihdr[8], 8);
+    assert_eq!(ihdr[9], color_type);
+    assert_eq!(&ihdr[10 ..], [0, 0, 0]);
+
+    assert_eq!([chunks[0].2, chunks[1].2], crcs);
+    assert_eq!(chunks[2].2, 0xAE42_6082);
+}
+
+#[test]
+fn png_gray() {
+    let image = Image(1, 1, vec![128, 128, 128, 255]);
+    assert_eq!(image.encode_png(), [
+        0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n',
+        0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0, 0x3a, 0x7e, 0x9b, 0x55,
+        // zlib header, one stored block with the filter byte and the pixel, adler-32
+        0, 0, 0, 13, b'I', b'D', b'A', b'T', 0x78, 0x01, 1, 2, 0, 0xfd, 0xff, 0, 128, 0x00, 0x82, 0x00, 0x81, 0xc3, 0x6e, 0x25, 0xe0,
+        0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82,
+    ][..]);
+    check_png(image, 0, [0x3a7e_9b55, 0xc36e_25e0]);
+}
+
+#[test]
+fn png_gray_alpha() {
+    check_png(Image(2, 1, vec![64, 64, 64, 128, 64, 64, 64, 0]), 4, [0x5e2b_b701, 0xc105_29e4]);
+}
+
+#[test]
+fn png_rgb() {
+    check_png(Image(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 255]), 2, [0x7b40_e8dd, 0x5536_bac7]);
+}
+
+#[test]
+fn png_rgba() {
+    check_png(Image(1, 2, vec![255, 0, 0, 128, 0, 255, 0, 255]), 6, [0x9981_b627, 0x87e1_6b22]);
+}
+
+#[test]
+fn png_splits_stored_blocks() {
+    // 300 rows of a filter byte and 300 black pixels
+    let png = Image(300, 300, [0, 0, 0, 255].repeat(300 * 300)).encode_png();
+    let chunks = chunks(&png);
+    let idat = chunks[1].1;
+    assert_eq!(&idat[.. 2], [0x78, 0x01]);
+
+    let mut rest = &idat[2 ..];
+    let mut lengths = vec![];
+    loop {
+        let last = rest[0] == 1;
+        let len = u16::from_le_bytes([rest[1], rest[2]]);
+        assert_eq!(u16::from_le_bytes([rest[3], rest[4]]), !len);
+        assert!(rest[5 .. 5 + len as usize].iter().all(|&b| b == 0));
+        lengths.push(len as usize);
+        rest = &rest[5 + len as usize ..];
+        if last {
+            break;
+        }
+    }
+    assert_eq!(lengths, [65535, 300 * 301 - 65535]);
+    // the adler-32 of n zeros is (n % 65521) << 16 | 1
+    assert_eq!(rest, ((300 * 301 % 65521) << 16 | 1u32).to_be_bytes());
+}
+
+#[test]
+fn ppm_and_pam() {
+    let image = Image(2, 1, vec![255, 0, 0, 128, 1, 2, 3, 255]);
+    assert_eq!(image.encode_ppm(), [&b"P6\n2 1\n255\n"[..], &[255, 0, 0, 1, 2, 3]].concat());
+    assert_eq!(image.encode_pam(), [
+        &b"P7\nWIDTH 2\nHEIGHT 1\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n"[..],
+        &[255, 0, 0, 128, 1, 2, 3, 255]
+    ].concat());
+}
+
+#[cfg(feature = "impl_raqote")]
+#[test]
+fn raqote_output_is_not_premultiplied() {
+    let mut target = raqote::DrawTarget::new(2, 1);
+    // premultiplied argb: half transparent red and a transparent pixel
+    target.get_data_mut().copy_from_slice(&[0x8080_0000, 0]);
+    assert_eq!(target.to_rgba8(), (2, 1, vec![255, 0, 0, 128, 0, 0, 0, 0]));
+    let pam = target.encode_pam();
+    assert_eq!(&pam[pam.len() - 8 ..], [255, 0, 0, 128, 0, 0, 0, 0]);
+    assert_eq!(chunks(&target.encode_png())[0].1[9], 6);
+}
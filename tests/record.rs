@@ -0,0 +1,117 @@
+mod common;
+
+use vector::*;
+use common::{Log, approx_rect};
+
+fn square(x: f32, y: f32, size: f32) -> Path {
+    let mut builder = PathBuilder::new();
+    builder.rect(Rect::new(Vector::new(x, y), Vector::splat(size)));
+    builder.into_outline()
+}
+
+fn bounds(path: &Path) -> Rect {
+    path.bounding_box().unwrap()
+}
+
+#[test]
+fn replay_translates_styles_clips_and_images() {
+    let mut recording = Recording::new(Vector::new(100.0, 100.0));
+    // two rows of one rgb pixel, padded to 4 bytes
+    let image = recording.texture_with_stride(1, 2, &[1, 2, 3, 0, 4, 5, 6, 0], 4, PixelFormat::Rgb24).unwrap();
+    let line = LineStyle::default(3.0);
+    let transform = Transform::from_scale(Vector::splat(2.0));
+    let solid = recording.build_style(PathStyle {
+        fill: Some(Paint::Solid((255, 0, 0, 255))),
+        stroke: Some((Paint::Image(image, transform), line)),
+        fill_rule: FillRule::EvenOdd,
+    });
+    let gradient = LinearGradient::new(Vector::new(0.0, 0.0), Vector::new(10.0, 0.0), vec![
+        GradientStop::new(0.0, (0, 0, 0, 255)),
+        GradientStop::new(1.0, (255, 255, 255, 255)),
+    ]);
+    let shaded = recording.build_style(PathStyle {
+        fill: Some(Paint::LinearGradient(gradient.clone())),
+        stroke: None,
+        fill_rule: FillRule::NonZero,
+    });
+    let small = recording.clip_path(square(0.0, 0.0, 10.0), FillRule::NonZero);
+    let large = recording.clip_path(square(0.0, 0.0, 50.0), FillRule::EvenOdd);
+
+    recording.draw_path(square(1.0, 1.0, 1.0), &solid, Some(&small));
+    let layer = recording.push_layer(0.5, BlendMode::Multiply, Some(&large));
+    recording.draw_path(square(2.0, 2.0, 2.0), &shaded, None);
+    recording.pop_layer(layer);
+    recording.draw_path(square(3.0, 3.0, 3.0), &shaded, Some(&large));
+
+    let mut log = Log::new(recording.size());
+    recording.replay(&mut log).unwrap();
+
+    // the image is handed over tightly packed
+    assert_eq!(log.textures.len(), 1);
+    let texture = &log.textures[0];
+    assert_eq!((texture.width, texture.height, texture.stride, texture.format), (1, 2, 3, PixelFormat::Rgb24));
+    assert_eq!(texture.data, [1, 2, 3, 4, 5, 6]);
+
+    assert_eq!(log.clips.len(), 2);
+    assert!(approx_rect(bounds(&log.clips[0].0), (0.0, 0.0), (10.0, 10.0), 0.0));
+    assert!(matches!(log.clips[0].1, FillRule::NonZero));
+    assert!(approx_rect(bounds(&log.clips[1].0), (0.0, 0.0), (50.0, 50.0), 0.0));
+    assert!(matches!(log.clips[1].1, FillRule::EvenOdd));
+
+    assert_eq!(log.layers, [(0.5, BlendMode::Multiply, Some(1))]);
+    assert_eq!(log.depth, 0);
+
+    assert_eq!(log.draws.len(), 3);
+    let draw = &log.draws[0];
+    assert!(approx_rect(bounds(&draw.path), (1.0, 1.0), (1.0, 1.0), 0.0));
+    assert_eq!((draw.clip, draw.depth), (Some(0), 0));
+    assert!(matches!(draw.style.fill, Some(Paint::Solid((255, 0, 0, 255)))));
+    assert!(matches!(draw.style.fill_rule, FillRule::EvenOdd));
+    match draw.style.stroke {
+        Some((Paint::Image(0, tr), l)) => assert_eq!((tr, l), (transform, line)),
+        ref stroke => panic!("{:?}", stroke.as_ref().map(|s| &s.0)),
+    }
+
+    let draw = &log.draws[1];
+    assert!(approx_rect(bounds(&draw.path), (2.0, 2.0), (2.0, 2.0), 0.0));
+    assert_eq!((draw.clip, draw.depth), (None, 1));
+    assert!(matches!(draw.style.fill, Some(Paint::LinearGradient(ref g)) if *g == gradient));
+    assert!(draw.style.stroke.is_none());
+
+    let draw = &log.draws[2];
+    assert!(approx_rect(bounds(&draw.path), (3.0, 3.0), (3.0, 3.0), 0.0));
+    assert_eq!((draw.clip, draw.depth), (Some(1), 0));
+}
+
+#[test]
+fn ids_of_other_recordings_are_ignored() {
+    let mut other = Recording::new(Vector::new(10.0, 10.0));
+    let foreign_style = other.build_style(PathStyle { fill: Some(Paint::black()), stroke: None, fill_rule: FillRule::NonZero });
+    let foreign_clip = other.clip_path(square(0.0, 0.0, 1.0), FillRule::NonZero);
+    let foreign_image = other.texture(1, 1, &[0], PixelFormat::L8).unwrap();
+
+    let mut recording = Recording::new(Vector::new(10.0, 10.0));
+    let image = recording.texture(1, 1, &[255], PixelFormat::L8).unwrap();
+    // the same index, but not the same image
+    assert_ne!(image, foreign_image);
+    let style = recording.build_style(PathStyle {
+        fill: Some(Paint::Image(foreign_image, Transform::default())),
+        stroke: Some((Paint::Image(image, Transform::default()), LineStyle::default(1.0))),
+        fill_rule: FillRule::NonZero,
+    });
+    recording.draw_path(square(0.0, 0.0, 1.0), &foreign_style, None);
+    recording.draw_path(square(0.0, 0.0, 2.0), &style, Some(&foreign_clip));
+    let layer = recording.push_layer(0.5, BlendMode::Normal, Some(&foreign_clip));
+    recording.pop_layer(layer);
+
+    let mut log = Log::new(recording.size());
+    recording.replay(&mut log).unwrap();
+    assert_eq!(log.draws.len(), 1);
+    let draw = &log.draws[0];
+    assert!(approx_rect(bounds(&draw.path), (0.0, 0.0), (2.0, 2.0), 0.0));
+    assert_eq!(draw.clip, None);
+    assert!(draw.style.fill.is_none());
+    assert!(matches!(draw.style.stroke, Some((Paint::Image(0, _), _))));
+    assert!(log.clips.is_empty());
+    assert_eq!(log.layers, [(0.5, BlendMode::Normal, None)]);
+}
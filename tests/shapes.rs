@@ -0,0 +1,122 @@
+mod common;
+
+use vector::*;
+use common::{check_shapes, approx_rect};
+
+#[test]
+fn path_shapes() {
+    check_shapes::<Path>();
+}
+
+#[cfg(feature = "impl_pathfinder")]
+#[test]
+fn pathfinder_shapes() {
+    check_shapes::<pathfinder_content::outline::Outline>();
+}
+
+#[cfg(feature = "impl_raqote")]
+#[test]
+fn raqote_shapes() {
+    check_shapes::<raqote::Path>();
+}
+
+fn shape(draw: impl Fn(&mut PathBuilder<Path>)) -> Vec<PathEvent> {
+    let mut builder = PathBuilder::new();
+    draw(&mut builder);
+    builder.into_outline().events().collect()
+}
+
+// the corners of a polygon, checking that it is a single closed contour of lines
+fn corners(events: &[PathEvent]) -> Vec<Vector> {
+    match events {
+        [PathEvent::MoveTo(first), lines @ .., PathEvent::Close] => {
+            let mut points = vec![*first];
+            for e in lines {
+                match *e {
+                    PathEvent::LineTo(p) => points.push(p),
+                    ref e => panic!("unexpected {:?} in {:?}", e, events),
+                }
+            }
+            points
+        }
+        _ => panic!("not a closed contour: {:?}", events),
+    }
+}
+
+fn bounds(draw: impl Fn(&mut PathBuilder<Path>)) -> Rect {
+    let mut builder = PathBuilder::new();
+    draw(&mut builder);
+    builder.into_outline().bounding_box().unwrap()
+}
+
+#[test]
+fn polygons() {
+    let points = [Vector::new(0.0, 0.0), Vector::new(4.0, 0.0), Vector::new(4.0, 3.0)];
+    assert_eq!(corners(&shape(|b| b.polygon(&points))), points);
+    assert!(shape(|b| b.polygon(&[])).is_empty());
+
+    let center = Vector::new(10.0, 20.0);
+    let hexagon = corners(&shape(|b| b.regular_polygon(center, 5.0, 6, 0.0)));
+    assert_eq!(hexagon.len(), 6);
+    for (i, &p) in hexagon.iter().enumerate() {
+        let angle = std::f32::consts::PI / 3.0 * i as f32;
+        assert!((p - center - Vector::new(angle.cos(), angle.sin()) * 5.0).length() < 1e-5, "{} {:?}", i, p);
+    }
+    // corners at 0° and 180°, the edges at 90° and 270° are 5·sin(60°) from the center
+    let h = 5.0 * (std::f32::consts::PI / 3.0).sin();
+    assert!(approx_rect(bounds(|b| b.regular_polygon(center, 5.0, 6, 0.0)), (5.0, 20.0 - h), (10.0, 2.0 * h), 1e-5));
+}
+
+#[test]
+fn stars() {
+    let center = Vector::new(0.0, 0.0);
+    let star = corners(&shape(|b| b.star(center, 10.0, 4.0, 5, 0.0)));
+    assert_eq!(star.len(), 10);
+    for (i, &p) in star.iter().enumerate() {
+        let radius = if i % 2 == 0 { 10.0 } else { 4.0 };
+        assert!((p.length() - radius).abs() < 1e-5, "{} {:?}", i, p);
+    }
+    // the tips at 0° and ±144° and the inner corners at ±108° span the x range
+    let x_min = 10.0 * (0.8 * std::f32::consts::PI).cos();
+    let y = 10.0 * (0.4 * std::f32::consts::PI).sin();
+    assert!(approx_rect(bounds(|b| b.star(center, 10.0, 4.0, 5, 0.0)), (x_min, -y), (10.0 - x_min, 2.0 * y), 1e-4));
+}
+
+#[test]
+fn superellipses() {
+    let center = Vector::new(50.0, 50.0);
+    let radius = Vector::new(20.0, 10.0);
+    for &exponent in &[0.5, 2.0, 4.0] {
+        let events = shape(|b| b.superellipse(center, radius, exponent));
+        // a move, 32 curves and the close
+        assert_eq!(events.len(), 34, "{}", exponent);
+        for e in &events[1 .. 33] {
+            match *e {
+                PathEvent::CubicTo(_, _, p) => {
+                    let d = p - center;
+                    let sum = (d.x() / radius.x()).abs().powf(exponent) + (d.y() / radius.y()).abs().powf(exponent);
+                    assert!((sum - 1.0).abs() < 1e-3, "{}: {:?}", exponent, p);
+                }
+                ref e => panic!("{}: {:?}", exponent, e),
+            }
+        }
+        // the spline overshoots the points a little
+        let r = bounds(|b| b.superellipse(center, radius, exponent));
+        assert!(approx_rect(r, (30.0, 40.0), (40.0, 20.0), 0.1), "{}: {:?}", exponent, r);
+    }
+    // approaching the rectangle
+    for &exponent in &[100.0, f32::INFINITY] {
+        assert_eq!(shape(|b| b.superellipse(center, radius, exponent)).len(), 34);
+    }
+}
+
+#[test]
+fn superellipse_rejects_non_positive_exponents() {
+    for &exponent in &[0.0, -2.0, f32::NAN, f32::NEG_INFINITY] {
+        let mut builder = PathBuilder::<Path>::new();
+        builder.move_to(Vector::new(1.0, 1.0));
+        builder.superellipse(Vector::new(50.0, 50.0), Vector::new(20.0, 10.0), exponent);
+        assert_eq!(builder.pos(), Some(Vector::new(1.0, 1.0)), "{}", exponent);
+        assert!(builder.into_outline().events().next().is_none(), "{}", exponent);
+    }
+}
@@ -0,0 +1,143 @@
+use vector::*;
+
+fn line(length: f32) -> Path {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.move_to(Vector::new(0.0, 0.0));
+    builder.line_to(Vector::new(length, 0.0));
+    builder.into_outline()
+}
+
+fn contours(path: &Path) -> usize {
+    path.events().filter(|e| matches!(e, PathEvent::MoveTo(_))).count()
+}
+
+#[test]
+fn dashes_split_the_line() {
+    let style = LineStyle::default(1.0).dashed(&[10.0, 10.0], 0.0);
+    let stroke: Path = line(100.0).stroke_to_fill(&style, 0.01);
+    assert_eq!(contours(&stroke), 5);
+}
+
+#[test]
+fn tiny_dashes_are_drawn_solid() {
+    // the pattern is far below the precision of the positions along the line
+    let style = LineStyle::default(1.0).dashed(&[1e-6], 0.0);
+    let stroke: Path = line(100.0).stroke_to_fill(&style, 0.01);
+    assert_eq!(contours(&stroke), 1);
+    let bounds = stroke.bounding_box().unwrap();
+    assert_eq!(bounds.width(), 100.0);
+
+    let style = LineStyle::default(1.0).dashed(&[100.0, 1e-9], 0.0);
+    let stroke: Path = line(1000.0).stroke_to_fill(&style, 0.01);
+    assert!(contours(&stroke) >= 10);
+}
+
+#[test]
+fn invalid_widths_draw_nothing() {
+    for &width in &[0.0, -1.0, f32::NAN] {
+        let stroke: Path = line(100.0).stroke_to_fill(&LineStyle::default(width), 0.01);
+        assert_eq!(contours(&stroke), 0);
+    }
+}
+
+fn polyline(points: &[(f32, f32)]) -> Path {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.move_to(Vector::new(points[0].0, points[0].1));
+    for &(x, y) in &points[1 ..] {
+        builder.line_to(Vector::new(x, y));
+    }
+    builder.into_outline()
+}
+
+// the signed area of the flattened outline, which is the integral of the winding number
+fn area(path: &Path) -> f32 {
+    let flat: Path = path.flatten(0.001);
+    let mut contours: Vec<Vec<Vector>> = vec![];
+    for event in flat.events() {
+        match event {
+            PathEvent::MoveTo(p) => contours.push(vec![p]),
+            PathEvent::LineTo(p) => contours.last_mut().unwrap().push(p),
+            PathEvent::Close => {}
+            e => panic!("{:?} after flattening", e),
+        }
+    }
+    contours.iter().map(|points| {
+        let n = points.len();
+        (0 .. n).map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.x() * b.y() - a.y() * b.x()
+        }).sum::<f32>() * 0.5
+    }).sum::<f32>().abs()
+}
+
+fn stroke(path: &Path, cap: LineCap, join: LineJoin) -> Path {
+    path.stroke_to_fill(&LineStyle { cap, join, ..LineStyle::default(2.0) }, 0.001)
+}
+
+// an L with a right angle at (10, 0)
+fn l_shape() -> Path {
+    polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)])
+}
+
+#[test]
+fn caps() {
+    let l = l_shape();
+    let butt = stroke(&l, LineCap::Butt, LineJoin::Bevel);
+    let square = stroke(&l, LineCap::Square, LineJoin::Bevel);
+    let round = stroke(&l, LineCap::Round, LineJoin::Bevel);
+
+    let bounds = |path: &Path| {
+        let r = path.bounding_box().unwrap();
+        [r.min_x(), r.min_y(), r.max_x(), r.max_y()]
+    };
+    assert_eq!(bounds(&butt), [0.0, -1.0, 11.0, 10.0]);
+    assert_eq!(bounds(&square), [-1.0, -1.0, 11.0, 11.0]);
+    let r = bounds(&round);
+    assert!((r[0] + 1.0).abs() < 1e-4 && (r[3] - 11.0).abs() < 1e-4, "{:?}", r);
+
+    // each square cap adds a 1 by 2 rectangle, each round one half of a unit circle
+    assert!((area(&square) - area(&butt) - 4.0).abs() < 1e-3);
+    assert!((area(&round) - area(&butt) - std::f32::consts::PI).abs() < 1e-2);
+}
+
+#[test]
+fn joins() {
+    let l = l_shape();
+    let bevel = area(&stroke(&l, LineCap::Butt, LineJoin::Bevel));
+    let round = area(&stroke(&l, LineCap::Butt, LineJoin::Round));
+    // the miter of a right angle is √2 times the width
+    let miter = stroke(&l, LineCap::Butt, LineJoin::Miter(1.5));
+    let r = miter.bounding_box().unwrap();
+    assert!((r.max_x() - 11.0).abs() < 1e-5 && (r.min_y() + 1.0).abs() < 1e-5, "{:?}", r);
+
+    // the bevel cuts off half of the unit square at the outer corner, the round join a quarter circle
+    assert!((area(&miter) - bevel - 0.5).abs() < 1e-3);
+    assert!((round - bevel - (std::f32::consts::FRAC_PI_4 - 0.5)).abs() < 1e-2);
+}
+
+#[test]
+fn miter_limit() {
+    // a turn of 120°, which leaves an angle θ = 60° between the segments.
+    // the miter is 1 / sin(θ/2) = 2 times the width.
+    let (s, c) = (60f32.to_radians().sin(), 60f32.to_radians().cos());
+    let sharp = polyline(&[(0.0, 0.0), (10.0, 0.0), (10.0 - 10.0 * c, 10.0 * s)]);
+    let bevel = area(&stroke(&sharp, LineCap::Butt, LineJoin::Bevel));
+    let below = stroke(&sharp, LineCap::Butt, LineJoin::Miter(1.9));
+    let above = stroke(&sharp, LineCap::Butt, LineJoin::Miter(2.1));
+
+    assert!((area(&below) - bevel).abs() < 1e-3);
+    assert_eq!(below.bounding_box(), stroke(&sharp, LineCap::Butt, LineJoin::Bevel).bounding_box());
+    // the tip is 2 away from the corner, along the bisector of the outer side
+    let tip = Vector::new(10.0 + 2.0 * s, -2.0 * c);
+    assert!((above.bounding_box().unwrap().max_x() - tip.x()).abs() < 1e-4);
+    // the normals turn by 120°. the kite of the miter is tan(60°), the bevel triangle sin(120°) / 2.
+    assert!((area(&above) - bevel - (60f32.to_radians().tan() - 0.5 * s)).abs() < 1e-3);
+}
+
+#[test]
+fn long_lines_keep_their_width() {
+    // the squared length of the line overflows
+    let line = polyline(&[(0.0, 0.0), (0.0, 1e20)]);
+    let r = stroke(&line, LineCap::Butt, LineJoin::Bevel).bounding_box().unwrap();
+    assert_eq!((r.min_x(), r.max_x(), r.min_y(), r.max_y()), (-1.0, 1.0, 0.0, 1e20));
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::*;
+use vector::*;
+use std::time::{Duration, Instant};
+
+fn svg(body: &str) -> String {
+    format!(r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="100" height="100">{}</svg>"#, body)
+}
+
+#[test]
+fn root_presentation_attributes_are_inherited() {
+    // the usual icon set markup
+    let log = draw_svg(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24" width="24" height="24"
+        fill="none" stroke="currentColor" stroke-width="2"><path d="M1 1 L10 10" /></svg>"#);
+    assert_eq!(log.draws.len(), 1);
+    let style = &log.draws[0].style;
+    assert!(style.fill.is_none());
+    match style.stroke {
+        Some((Paint::Solid((0, 0, 0, 255)), ref line)) => assert_eq!(line.width, 2.0),
+        _ => panic!("expected a black stroke")
+    }
+}
+
+#[test]
+fn malformed_colors_do_not_panic() {
+    for color in &["rgb)x(", "rgb(", "rgb)", "rgba(1,2)", "#", "#12", "rgb(1,2,3"] {
+        let log = draw_svg(&svg(&format!(r#"<rect width="10" height="10" fill="{}" />"#, color)));
+        assert!(log.draws.is_empty(), "{}", color);
+    }
+    let log = draw_svg(&svg(r#"<rect width="10" height="10" fill="rgb(255, 0, 50%)" />"#));
+    assert!(matches!(log.draws[0].style.fill, Some(Paint::Solid((255, 0, 128, 255)))));
+}
+
+#[test]
+fn use_instantiates_symbols() {
+    let log = draw_svg(&svg(r##"
+        <defs><symbol id="s" viewBox="0 0 10 10"><rect width="10" height="10" /></symbol></defs>
+        <use href="#s" x="5" y="5" width="20" height="20" />
+        <use xlink:href="#s" x="50" width="40" height="20" />
+    "##));
+    assert_eq!(log.draws.len(), 2);
+    let bounds: Vec<Rect> = log.draws.iter().map(|d| d.path.bounding_box().unwrap()).collect();
+    assert!(approx_rect(bounds[0], (5.0, 5.0), (20.0, 20.0), 1e-4), "{:?}", bounds[0]);
+    // xMidYMid meet centers the square view box horizontally
+    assert!(approx_rect(bounds[1], (60.0, 0.0), (20.0, 20.0), 1e-4), "{:?}", bounds[1]);
+    // both instances clip to their viewport
+    assert_eq!(log.layers.len(), 2);
+    let clip = log.clips[log.layers[1].2.unwrap()].0.bounding_box().unwrap();
+    assert!(approx_rect(clip, (50.0, 0.0), (40.0, 20.0), 1e-4), "{:?}", clip);
+}
+
+#[test]
+fn symbols_are_not_drawn_directly() {
+    let log = draw_svg(&svg(r#"<symbol id="s"><rect width="10" height="10" /></symbol>"#));
+    assert!(log.draws.is_empty());
+}
+
+#[test]
+fn nested_use_fan_out_is_limited() {
+    // every level uses the previous one ten times, 10^9 rects in total
+    let mut body = String::from(r#"<rect id="l0" width="1" height="1" />"#);
+    for level in 1 ..= 9 {
+        body.push_str(&format!(r#"<g id="l{}">"#, level));
+        for _ in 0 .. 10 {
+            body.push_str(&format!(r##"<use href="#l{}" />"##, level - 1));
+        }
+        body.push_str("</g>");
+    }
+    let start = Instant::now();
+    let log = draw_svg(&svg(&body));
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(log.draws.len() < 1_000_000);
+}
+
+#[test]
+fn deep_groups_are_drawn() {
+    let depth = 100;
+    let body = format!("{}<rect width=\"1\" height=\"1\" />{}", "<g>".repeat(depth), "</g>".repeat(depth));
+    assert_eq!(draw_svg(&svg(&body)).draws.len(), 1);
+}
+
+#[test]
+fn use_cycles_terminate() {
+    let log = draw_svg(&svg(r##"<g id="a"><rect width="1" height="1" /><use href="#a" /></g>"##));
+    assert!(!log.draws.is_empty());
+}
+
+#[test]
+fn clip_path_object_bounding_box_on_groups() {
+    let log = draw_svg(&svg(r#"
+        <clipPath id="c" clipPathUnits="objectBoundingBox"><rect width="0.5" height="1" /></clipPath>
+        <g clip-path="url(#c)"><rect x="10" y="10" width="20" height="10" /><rect x="30" y="20" width="10" height="10" /></g>
+    "#));
+    assert_eq!(log.draws.len(), 2);
+    assert_eq!(log.layers.len(), 1);
+    // the group covers 10,10 to 40,30
+    let clip = log.clips[log.layers[0].2.unwrap()].0.bounding_box().unwrap();
+    assert!(approx_rect(clip, (10.0, 10.0), (15.0, 20.0), 1e-3), "{:?}", clip);
+}
+
+#[test]
+fn clip_children_use_their_own_clip_rule() {
+    // a self-intersecting star has a hole with evenodd, but not with nonzero
+    let star = "M50 0 L79 90 L2 35 L98 35 L21 90 Z";
+    let log = draw_svg(&svg(&format!(r#"
+        <clipPath id="c"><path clip-rule="evenodd" d="{0}" /><path d="{0}" transform="translate(200 0)" /></clipPath>
+        <rect width="400" height="100" clip-path="url(#c)" />
+    "#, star)));
+    let (ref clip, rule) = log.clips[log.layers[0].2.unwrap()];
+    let center = Vector::new(50.0, 50.0);
+    assert!(!clip.contains(center, rule, 0.01));
+    assert!(clip.contains(center + Vector::new(200.0, 0.0), rule, 0.01));
+    assert!(clip.contains(Vector::new(50.0, 10.0), rule, 0.01));
+}
@@ -0,0 +1,126 @@
+use vector::*;
+
+fn v(x: f32, y: f32) -> Vector {
+    Vector::new(x, y)
+}
+
+fn parse(data: &str) -> Vec<PathEvent> {
+    let mut builder = PathBuilder::<Path>::new();
+    parse_path_data(data, &mut builder).unwrap();
+    builder.into_outline().events().collect()
+}
+
+fn error(data: &str) -> PathDataError {
+    let mut builder = PathBuilder::<Path>::new();
+    parse_path_data(data, &mut builder).unwrap_err()
+}
+
+#[test]
+fn implicit_repeats() {
+    // numbers after a move are lines of the same kind
+    assert_eq!(parse("M1 2 3 4 5 6"), [PathEvent::MoveTo(v(1.0, 2.0)), PathEvent::LineTo(v(3.0, 4.0)), PathEvent::LineTo(v(5.0, 6.0))]);
+    assert_eq!(parse("m1 2 3 4 5 6"), [PathEvent::MoveTo(v(1.0, 2.0)), PathEvent::LineTo(v(4.0, 6.0)), PathEvent::LineTo(v(9.0, 12.0))]);
+    // other commands repeat themselves
+    assert_eq!(parse("M0 0 h1 2 v3"), [
+        PathEvent::MoveTo(v(0.0, 0.0)), PathEvent::LineTo(v(1.0, 0.0)), PathEvent::LineTo(v(3.0, 0.0)), PathEvent::LineTo(v(3.0, 3.0))
+    ]);
+    assert_eq!(parse("M0 0 Q1 1 2 0 3 -1 4 0"), [
+        PathEvent::MoveTo(v(0.0, 0.0)), PathEvent::QuadTo(v(1.0, 1.0), v(2.0, 0.0)), PathEvent::QuadTo(v(3.0, -1.0), v(4.0, 0.0))
+    ]);
+}
+
+#[test]
+fn compact_numbers() {
+    assert_eq!(parse("M0.5.5-1e2,3"), [PathEvent::MoveTo(v(0.5, 0.5)), PathEvent::LineTo(v(-100.0, 3.0))]);
+    assert_eq!(parse("M-.5+1E1L1e-1-2"), [PathEvent::MoveTo(v(-0.5, 10.0)), PathEvent::LineTo(v(0.1, -2.0))]);
+    assert_eq!(parse(" M 1 , 2 \n L\t3,4 "), [PathEvent::MoveTo(v(1.0, 2.0)), PathEvent::LineTo(v(3.0, 4.0))]);
+}
+
+#[test]
+fn packed_arc_flags() {
+    let spaced = parse("M0 0 a1 1 0 0 0 1 1");
+    assert_eq!(parse("M0 0 a1 1 0 00 1 1"), spaced);
+    assert_eq!(parse("M0 0a1,1,0,001,1"), spaced);
+    // the flags and the end point run together
+    let sweep = parse("M0 0 a1 1 0 0 1 1 1");
+    assert_eq!(parse("M0 0 a1 1 0 011 1"), sweep);
+    assert_ne!(sweep, spaced);
+    for events in &[spaced, sweep] {
+        match events.last() {
+            Some(&PathEvent::CubicTo(_, _, p)) => assert!((p - v(1.0, 1.0)).length() < 1e-5, "{:?}", p),
+            e => panic!("{:?}", e),
+        }
+    }
+    assert_eq!(error("M0 0 a1 1 0 2 0 1 1").kind, PathDataErrorKind::ExpectedFlag);
+}
+
+#[test]
+fn horizontal_and_vertical_lines() {
+    assert_eq!(parse("M1 2 H5 V7 h-1 v-1"), [
+        PathEvent::MoveTo(v(1.0, 2.0)), PathEvent::LineTo(v(5.0, 2.0)), PathEvent::LineTo(v(5.0, 7.0)),
+        PathEvent::LineTo(v(4.0, 7.0)), PathEvent::LineTo(v(4.0, 6.0))
+    ]);
+}
+
+#[test]
+fn smooth_curves_reflect_the_control_point() {
+    let cubic = [
+        PathEvent::MoveTo(v(0.0, 0.0)),
+        PathEvent::CubicTo(v(1.0, 1.0), v(2.0, 1.0), v(3.0, 0.0)),
+        PathEvent::CubicTo(v(4.0, -1.0), v(5.0, -1.0), v(6.0, 0.0)),
+    ];
+    assert_eq!(parse("M0 0 C1 1 2 1 3 0 S5 -1 6 0"), cubic);
+    assert_eq!(parse("M0 0 c1 1 2 1 3 0 s2 -1 3 0"), cubic);
+
+    let quadratic = [
+        PathEvent::MoveTo(v(0.0, 0.0)),
+        PathEvent::QuadTo(v(1.0, 1.0), v(2.0, 0.0)),
+        PathEvent::QuadTo(v(3.0, -1.0), v(4.0, 0.0)),
+        PathEvent::QuadTo(v(5.0, 1.0), v(6.0, 0.0)),
+    ];
+    assert_eq!(parse("M0 0 Q1 1 2 0 T4 0 6 0"), quadratic);
+    assert_eq!(parse("M0 0 q1 1 2 0 t2 0 t2 0"), quadratic);
+
+    // without a previous curve of the same kind the first control point is the current point
+    assert_eq!(parse("M0 0 L1 1 S3 2 4 0"), [
+        PathEvent::MoveTo(v(0.0, 0.0)), PathEvent::LineTo(v(1.0, 1.0)), PathEvent::CubicTo(v(1.0, 1.0), v(3.0, 2.0), v(4.0, 0.0))
+    ]);
+    assert_eq!(parse("M0 0 Q1 1 2 0 S3 2 4 0"), [
+        PathEvent::MoveTo(v(0.0, 0.0)), PathEvent::QuadTo(v(1.0, 1.0), v(2.0, 0.0)), PathEvent::CubicTo(v(2.0, 0.0), v(3.0, 2.0), v(4.0, 0.0))
+    ]);
+    assert_eq!(parse("M0 0 T2 0"), [PathEvent::MoveTo(v(0.0, 0.0)), PathEvent::QuadTo(v(0.0, 0.0), v(2.0, 0.0))]);
+}
+
+#[test]
+fn close_starts_over_at_the_start_point() {
+    assert_eq!(parse("M1 1 l2 0 0 2 z l1 0"), [
+        PathEvent::MoveTo(v(1.0, 1.0)), PathEvent::LineTo(v(3.0, 1.0)), PathEvent::LineTo(v(3.0, 3.0)), PathEvent::Close,
+        PathEvent::MoveTo(v(1.0, 1.0)), PathEvent::LineTo(v(2.0, 1.0))
+    ]);
+}
+
+#[test]
+fn leading_relative_move_is_absolute() {
+    let mut builder = PathBuilder::<Path>::new();
+    builder.move_to(v(10.0, 10.0));
+    builder.line_to(v(20.0, 20.0));
+    // later moves are relative
+    parse_path_data("m1 2 3 4 m1 1 1 0", &mut builder).unwrap();
+    let events: Vec<PathEvent> = builder.into_outline().events().collect();
+    assert_eq!(events[2 ..], [
+        PathEvent::MoveTo(v(1.0, 2.0)), PathEvent::LineTo(v(4.0, 6.0)),
+        PathEvent::MoveTo(v(5.0, 7.0)), PathEvent::LineTo(v(6.0, 7.0))
+    ]);
+}
+
+#[test]
+fn error_positions() {
+    assert_eq!(error("L1 2"), PathDataError { position: 0, kind: PathDataErrorKind::ExpectedMoveTo });
+    assert_eq!(error("  l 1 2"), PathDataError { position: 2, kind: PathDataErrorKind::ExpectedMoveTo });
+    assert_eq!(error("1 2"), PathDataError { position: 0, kind: PathDataErrorKind::UnexpectedCharacter('1') });
+    assert_eq!(error("M1 2 x"), PathDataError { position: 5, kind: PathDataErrorKind::UnexpectedCharacter('x') });
+    assert_eq!(error("M1 2 L3 4 Z 5"), PathDataError { position: 12, kind: PathDataErrorKind::UnexpectedCharacter('5') });
+    assert_eq!(error("M1 2 é"), PathDataError { position: 5, kind: PathDataErrorKind::UnexpectedCharacter('é') });
+    assert_eq!(error("M1 2 L3"), PathDataError { position: 7, kind: PathDataErrorKind::ExpectedNumber });
+    assert_eq!(error("M1 2 L3 1e"), PathDataError { position: 9, kind: PathDataErrorKind::UnexpectedCharacter('e') });
+}
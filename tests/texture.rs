@@ -0,0 +1,84 @@
+use vector::*;
+
+// invalid sizes are rejected before anything is allocated for them
+fn check_validation<S: Surface>() {
+    let mut surface = S::new(Vector::new(10.0, 10.0));
+    assert!(matches!(surface.texture_with_stride(u32::MAX, u32::MAX, &[], 0, PixelFormat::Rgba32), Err(TextureError::StrideTooSmall { .. })));
+    assert!(matches!(surface.texture_with_stride(u32::MAX, u32::MAX, &[], usize::MAX, PixelFormat::Rgba32), Err(TextureError::TooLarge)));
+    assert!(matches!(surface.texture_with_stride(1 << 30, 1 << 28, &[0; 16], 4 << 30, PixelFormat::Rgba32), Err(TextureError::WrongLength { .. })));
+    assert_eq!(surface.texture_with_stride(0, 10, &[], 0, PixelFormat::L8).err(), Some(TextureError::ZeroSize));
+    assert!(surface.texture_with_stride(2, 2, &[0; 16], 8, PixelFormat::Rgba32).is_ok());
+}
+
+#[test]
+fn recording_texture_validation() {
+    check_validation::<Recording>();
+}
+
+#[cfg(feature = "impl_raqote")]
+#[test]
+fn raqote_texture_validation() {
+    check_validation::<raqote::DrawTarget>();
+}
+
+#[cfg(feature = "impl_pathfinder")]
+#[test]
+fn pathfinder_texture_validation() {
+    check_validation::<pathfinder_renderer::scene::Scene>();
+}
+
+#[cfg(feature = "impl_svg")]
+#[test]
+fn svg_texture_validation() {
+    check_validation::<Svg>();
+}
+
+#[test]
+fn convert_pixels_validates_first() {
+    assert!(matches!(convert_pixels(u32::MAX, u32::MAX, &[], 0, PixelFormat::Rgba32), Err(TextureError::StrideTooSmall { .. })));
+    assert!(matches!(convert_pixels(u32::MAX, u32::MAX, &[], usize::MAX, PixelFormat::L8), Err(TextureError::TooLarge)));
+    assert!(matches!(convert_pixels(1 << 30, 1 << 28, &[0; 16], 1 << 30, PixelFormat::L8), Err(TextureError::WrongLength { .. })));
+    // two gray pixels per row, padded to 4 bytes
+    let rgba = convert_pixels(2, 2, &[0, 255, 9, 9, 128, 64, 9, 9], 4, PixelFormat::L8).unwrap();
+    assert_eq!(rgba, [0, 0, 0, 255, 255, 255, 255, 255, 128, 128, 128, 255, 64, 64, 64, 255]);
+}
+
+#[cfg(feature = "impl_pdf")]
+#[test]
+fn pdf_texture_validation() {
+    check_validation::<Pdf>();
+}
+
+#[cfg(feature = "impl_svg")]
+#[test]
+fn svg_embeds_png_images() {
+    let mut svg = Svg::new(Vector::new(10.0, 10.0));
+    let gray = svg.texture(1, 1, &[128], PixelFormat::L8).unwrap();
+    svg.texture(1, 1, &[64, 128], PixelFormat::La16).unwrap();
+    svg.texture(1, 1, &[1, 2, 3], PixelFormat::Rgb24).unwrap();
+
+    // maps (x, y) to (a x + c y + e, b x + d y + f)
+    let tr = Transform::row_major(2.0, 0.5, -1.0, 3.0, 5.0, 6.0);
+    let (o, x, y) = (tr * Vector::new(0.0, 0.0), tr * Vector::new(1.0, 0.0), tr * Vector::new(0.0, 1.0));
+    let matrix = format!("matrix({} {} {} {} {} {})", x.x() - o.x(), x.y() - o.y(), y.x() - o.x(), y.y() - o.y(), o.x(), o.y());
+    svg.build_style(PathStyle { fill: Some(Paint::Image(gray, tr)), stroke: None, fill_rule: FillRule::NonZero });
+    let out = svg.finish();
+
+    // 70, 71 and 72 bytes long, so all three ways to end base64 are covered
+    let images = [
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAAAAAA6fptVAAAADUlEQVR4AQECAP3/AIAAggCBw24l4AAAAABJRU5ErkJggg==",
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAADklEQVR4AQEDAPz/AECAAQMAwTdh+tUAAAAASUVORK5CYII=",
+        "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAIAAACQd1PeAAAAD0lEQVR4AQEEAPv/AAECAwAOAActojPsAAAAAElFTkSuQmCC",
+    ];
+    for (i, data) in images.iter().enumerate() {
+        let image = format!("<defs><image id=\"img_{}\" width=\"1\" height=\"1\" href=\"data:image/png;base64,{}\" /></defs>\n", i, data);
+        assert!(out.contains(&image), "{}\n{}", image, out);
+    }
+    let pattern = format!(
+        "<pattern id=\"pat_3\" patternUnits=\"userSpaceOnUse\" width=\"1\" height=\"1\" patternTransform=\"{}\"><use href=\"#img_0\" /></pattern>\n",
+        matrix
+    );
+    assert!(out.contains(&pattern), "{}\n{}", pattern, out);
+    // not the identity
+    assert!(!pattern.contains("matrix(1 0 0 1 0 0)"));
+}